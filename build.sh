@@ -1,6 +1,6 @@
 #!/bin/sh
 
-for day in day*/; do
+for day in intcode/ day*/; do
     cd $day
     echo "---- $day ----"
     cargo build
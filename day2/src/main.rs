@@ -1,77 +1,21 @@
+use std::collections::VecDeque;
 use std::env;
-use std::fs;
 
-#[derive(Debug)]
-enum Instruction {
-    Add(u32, u32, u32),
-    Mul(u32, u32, u32),
-    End,
-}
-
-#[derive(Debug)]
-struct IntcodeComputer {
-    pc: usize,
-    program: Vec<u32>,
-}
-
-impl IntcodeComputer {
-    fn new(program: Vec<u32>) -> IntcodeComputer {
-        IntcodeComputer {
-            pc: 0,
-            program,
-        }
-    }
-
-    fn current_instruction(&self) -> Instruction {
-        match self.get(self.pc) {
-            1 => Instruction::Add(self.parameter(0), self.parameter(1), self.parameter(2)),
-            2 => Instruction::Mul(self.parameter(0), self.parameter(1), self.parameter(2)),
-            99 => Instruction::End,
-            _ => panic!("Unexpected instruction"),
-        }
-    }
-
-    fn get(&self, addr: usize) -> u32 {
-        self.program[addr]
-    }
-
-    fn parameter(&self, parameter: usize) -> u32 {
-        self.get(self.pc + 1 + parameter)
-    }
-
-    fn set(&mut self, index: usize, value: u32) {
-        self.program[index] = value;
-    }
+use intcode::{load_program, IntcodeComputer};
 
-    fn step(&mut self, amount: usize) {
-        self.pc += amount;
-    }
-
-    fn run(&mut self) -> u32 {
-        loop {
-            match self.current_instruction() {
-                Instruction::Add(a, b, c) => {
-                    self.set(c as usize, self.get(a as usize) + self.get(b as usize));
-                    self.step(4);
-                },
-                Instruction::Mul(a, b, c) => {
-                    self.set(c as usize, self.get(a as usize) * self.get(b as usize));
-                    self.step(4);
-                },
-                Instruction::End => break self.get(0),
-            }
-        }
-    }
+fn run(computer: &mut IntcodeComputer) -> i64 {
+    computer.run(&mut VecDeque::new(), &mut Vec::new()).expect("Program failed");
+    computer.get(0)
 }
 
-fn solve_inputs(program: Vec<u32>, target: u32) -> Option<(u32, u32)> {
+fn solve_inputs(program: Vec<i64>, target: i64) -> Option<(i64, i64)> {
     for noun in 0..100 {
         for verb in 0..100 {
             let mut computer = IntcodeComputer::new(program.clone());
-            computer.set(1, noun);
-            computer.set(2, verb);
+            computer.set(1, noun).expect("Program too small");
+            computer.set(2, verb).expect("Program too small");
 
-            if computer.run() == target {
+            if run(&mut computer) == target {
                 return Some((noun, verb));
             }
         }
@@ -82,13 +26,10 @@ fn solve_inputs(program: Vec<u32>, target: u32) -> Option<(u32, u32)> {
 fn main() {
     let args: Vec<String> = env::args().collect();
     let input_file = args.get(1).expect("Input file required");
-    let target: u32 = args.get(2).expect("Target value required")
+    let target: i64 = args.get(2).expect("Target value required")
         .parse().expect("Expected a number for target value");
 
-    let program: Vec<u32> = fs::read_to_string(input_file).expect("Unable to read input file")
-        .split(',')
-        .map(|x| x.parse().unwrap())
-        .collect();
+    let program = load_program(input_file).expect("Unable to read input file");
 
     match solve_inputs(program, target) {
         Some((noun, verb)) => println!("Noun: {}, Verb: {}, Result: {}", noun, verb, 100 * noun + verb),
@@ -108,10 +49,10 @@ mod tests {
                     let (program, expected_program, expected_output) = $value;
                     let mut computer = IntcodeComputer::new(program.to_vec());
 
-                    let output = computer.run();
+                    let output = run(&mut computer);
 
                     assert_eq!(output, expected_output);
-                    assert_eq!(computer.program, expected_program.to_vec());
+                    assert_eq!(computer.program(), &expected_program[..]);
                 }
             )*
         }
@@ -0,0 +1,19 @@
+use std::env;
+
+use intcode::{load_program, Network};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let input_file = args.get(1).expect("Input file required");
+    let program = load_program(input_file).expect("Unable to read input file");
+
+    let report = Network::new(&program, 50).run().expect("Program failed");
+    match report.monitor_packets.first() {
+        Some(packet) => println!("First Y sent to 255: {}", packet.y),
+        None => println!("Nothing was sent to 255"),
+    }
+    match report.repeated {
+        Some(y) => println!("First Y resent twice in a row: {}", y),
+        None => println!("No Y was resent twice in a row"),
+    }
+}
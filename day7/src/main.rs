@@ -0,0 +1,15 @@
+use std::env;
+
+use intcode::{best_chain, best_feedback_loop, load_program};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let input_file = args.get(1).expect("Input file required");
+    let program = load_program(input_file).expect("Unable to read input file");
+
+    let (signal, phases) = best_chain(&program, &[0, 1, 2, 3, 4]).expect("Program failed");
+    println!("Highest signal: {}, Phases: {:?}", signal, phases);
+
+    let (signal, phases) = best_feedback_loop(&program, &[5, 6, 7, 8, 9]).expect("Program failed");
+    println!("Highest feedback loop signal: {}, Phases: {:?}", signal, phases);
+}
@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+
+use crate::computer::{IntcodeComputer, Status};
+use crate::error::{ErrorKind, IntcodeError};
+use crate::Value;
+
+/// Every ordering of `values`.
+pub fn permutations(values: &[Value]) -> Vec<Vec<Value>> {
+    if values.is_empty() {
+        return vec!(Vec::new());
+    }
+
+    let mut result = Vec::new();
+    for (i, &first) in values.iter().enumerate() {
+        let mut rest = values.to_vec();
+        rest.remove(i);
+        for mut permutation in permutations(&rest) {
+            permutation.insert(0, first);
+            result.push(permutation);
+        }
+    }
+    result
+}
+
+/// Runs a copy of `program` for each phase setting, in series. Each amplifier reads its phase
+/// setting and then the signal from the one before, the first getting 0. Returns the signal
+/// output by the last amplifier.
+pub fn run_chain(program: &[Value], phases: &[Value]) -> Result<Value, IntcodeError> {
+    let mut signal = 0;
+    for &phase in phases {
+        let mut computer = IntcodeComputer::new(program.to_vec());
+        let mut output = Vec::new();
+        computer.run(&mut VecDeque::from(vec!(phase, signal)), &mut output)?;
+        signal = *output.last().ok_or_else(|| IntcodeError::new(computer.pc(), ErrorKind::NoOutput))?;
+    }
+    Ok(signal)
+}
+
+/// Runs a copy of `program` for each phase setting in a loop, where the last amplifier's
+/// output feeds back into the first. Each amplifier waits whenever it needs input, and the
+/// loop carries on until they have all halted. Returns the last signal sent to the thrusters
+/// by the final amplifier.
+pub fn run_feedback_loop(program: &[Value], phases: &[Value]) -> Result<Value, IntcodeError> {
+    let mut amplifiers: Vec<IntcodeComputer> = phases.iter()
+        .map(|&phase| {
+            let mut computer = IntcodeComputer::new(program.to_vec());
+            computer.push_input(phase);
+            computer
+        })
+        .collect();
+    if amplifiers.is_empty() {
+        return Ok(0);
+    }
+    amplifiers[0].push_input(0);
+
+    let count = amplifiers.len();
+    let mut halted = vec!(false; count);
+    let mut signal = None;
+    let mut last_round = None;
+    let mut current = 0;
+
+    while halted.contains(&false) {
+        match amplifiers[current].resume()? {
+            Status::Output(value) => {
+                amplifiers[(current + 1) % count].push_input(value);
+                if current == count - 1 {
+                    signal = Some(value);
+                }
+                continue;
+            },
+            Status::Halted => halted[current] = true,
+            Status::NeedsInput | Status::Watchpoint(_) => (),
+            Status::Running => unreachable!(),
+        }
+
+        current = (current + 1) % count;
+        if current == 0 {
+            // A whole round without executing anything means every amplifier is stuck waiting
+            let steps: u64 = amplifiers.iter().map(|amplifier| amplifier.steps()).sum();
+            if last_round == Some(steps) {
+                let waiting = halted.iter().position(|h| !h).unwrap();
+                return Err(IntcodeError::new(amplifiers[waiting].pc(), ErrorKind::NoInput));
+            }
+            last_round = Some(steps);
+        }
+    }
+
+    let last = &amplifiers[count - 1];
+    signal.ok_or_else(|| IntcodeError::new(last.pc(), ErrorKind::NoOutput))
+}
+
+/// Tries `run` with every ordering of `phases`, returning the highest signal and the phase
+/// settings that gave it.
+pub(crate) fn best<F>(phases: &[Value], run: F) -> Result<(Value, Vec<Value>), IntcodeError>
+where
+    F: Fn(&[Value]) -> Result<Value, IntcodeError>,
+{
+    let mut best: Option<(Value, Vec<Value>)> = None;
+    for permutation in permutations(phases) {
+        let signal = run(&permutation)?;
+        match &best {
+            Some((highest, _)) if *highest >= signal => (),
+            _ => best = Some((signal, permutation)),
+        }
+    }
+    Ok(best.expect("there is always at least one permutation"))
+}
+
+/// Searches every ordering of `phases` for the one that gives the highest signal from
+/// `run_chain`.
+pub fn best_chain(program: &[Value], phases: &[Value]) -> Result<(Value, Vec<Value>), IntcodeError> {
+    best(phases, |permutation| run_chain(program, permutation))
+}
+
+/// Searches every ordering of `phases` for the one that gives the highest signal from
+/// `run_feedback_loop`.
+pub fn best_feedback_loop(program: &[Value], phases: &[Value]) -> Result<(Value, Vec<Value>), IntcodeError> {
+    best(phases, |permutation| run_feedback_loop(program, permutation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{AMPLIFIER_CHAIN, AMPLIFIER_FEEDBACK, READS_TWO_SENDS_ONE};
+
+    #[test]
+    fn test_permutations() {
+        assert_eq!(permutations(&[]), vec!(vec!()));
+        assert_eq!(permutations(&[1, 2, 3]), vec!(
+            vec!(1, 2, 3),
+            vec!(1, 3, 2),
+            vec!(2, 1, 3),
+            vec!(2, 3, 1),
+            vec!(3, 1, 2),
+            vec!(3, 2, 1),
+        ));
+        assert_eq!(permutations(&[0, 1, 2, 3, 4]).len(), 120);
+    }
+
+    macro_rules! test_best_chain {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (program, expected_signal, expected_phases): (Vec<Value>, Value, Vec<Value>) = $value;
+
+                    assert_eq!(run_chain(&program, &expected_phases), Ok(expected_signal));
+                    assert_eq!(best_chain(&program, &[0, 1, 2, 3, 4]), Ok((expected_signal, expected_phases)));
+                }
+            )*
+        }
+    }
+
+    test_best_chain! {
+        test_best_chain_1: (
+            AMPLIFIER_CHAIN.to_vec(),
+            43210,
+            vec!(4, 3, 2, 1, 0),
+        ),
+        test_best_chain_2: (
+            vec!(3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4, 23, 99, 0, 0),
+            54321,
+            vec!(0, 1, 2, 3, 4),
+        ),
+        test_best_chain_3: (
+            vec!(
+                3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7, 33, 1, 33, 31, 31, 1,
+                32, 31, 31, 4, 31, 99, 0, 0, 0,
+            ),
+            65210,
+            vec!(1, 0, 4, 3, 2),
+        ),
+    }
+
+    macro_rules! test_best_feedback_loop {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (program, expected_signal, expected_phases): (Vec<Value>, Value, Vec<Value>) = $value;
+
+                    assert_eq!(run_feedback_loop(&program, &expected_phases), Ok(expected_signal));
+                    assert_eq!(
+                        best_feedback_loop(&program, &[5, 6, 7, 8, 9]),
+                        Ok((expected_signal, expected_phases))
+                    );
+                }
+            )*
+        }
+    }
+
+    test_best_feedback_loop! {
+        test_best_feedback_loop_1: (
+            AMPLIFIER_FEEDBACK.to_vec(),
+            139629729,
+            vec!(9, 8, 7, 6, 5),
+        ),
+        test_best_feedback_loop_2: (
+            vec!(
+                3, 52, 1001, 52, -5, 52, 3, 53, 1, 52, 56, 54, 1007, 54, 5, 55, 1005, 55, 26, 1001, 54, -5, 54, 1105,
+                1, 12, 1, 53, 54, 53, 1008, 54, 0, 55, 1001, 55, 1, 55, 2, 53, 55, 53, 4, 53, 1001, 56, -1, 56, 1005,
+                56, 6, 99, 0, 0, 0, 0, 10,
+            ),
+            18216,
+            vec!(9, 7, 8, 5, 6),
+        ),
+    }
+
+    #[test]
+    fn test_feedback_loop_is_a_chain_for_one_shot_programs() {
+        assert_eq!(run_feedback_loop(&AMPLIFIER_CHAIN, &[4, 3, 2, 1, 0]), Ok(43210));
+    }
+
+    #[test]
+    fn test_feedback_loop_deadlock() {
+        assert_eq!(
+            run_feedback_loop(&READS_TWO_SENDS_ONE, &[1, 2]),
+            Err(IntcodeError::new(4, ErrorKind::NoInput))
+        );
+    }
+
+    #[test]
+    fn test_run_chain_without_output() {
+        assert_eq!(run_chain(&[3, 0, 3, 0, 99], &[1]), Err(IntcodeError::new(4, ErrorKind::NoOutput)));
+    }
+}
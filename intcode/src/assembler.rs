@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::instruction::{
+    ADD, ADJUSTRELATIVEBASE, EQUALS, HALT, INPUT, JUMPIFFALSE, JUMPIFTRUE, LESSTHAN, MULTIPLY, OUTPUT,
+};
+use crate::Value;
+
+/// Each mnemonic with its opcode, parameter count and the parameter it writes to, if any.
+const MNEMONICS: [(&str, Value, usize, Option<usize>); 10] = [
+    ("ADD", ADD, 3, Some(3)),
+    ("MUL", MULTIPLY, 3, Some(3)),
+    ("IN", INPUT, 1, Some(1)),
+    ("OUT", OUTPUT, 1, None),
+    ("JT", JUMPIFTRUE, 2, None),
+    ("JF", JUMPIFFALSE, 2, None),
+    ("LT", LESSTHAN, 3, Some(3)),
+    ("EQ", EQUALS, 3, Some(3)),
+    ("ARB", ADJUSTRELATIVEBASE, 1, None),
+    ("HLT", HALT, 0, None),
+];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct AssembleError {
+    /// The source line the error was found on, counting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AssembleError {}
+
+enum Statement<'a> {
+    Empty,
+    Constant(&'a str, &'a str),
+    Instruction(Value, Option<usize>, Vec<&'a str>),
+    Data(Vec<&'a str>),
+}
+
+struct Line<'a> {
+    number: usize,
+    label: Option<&'a str>,
+    statement: Statement<'a>,
+}
+
+impl<'a> Line<'a> {
+    fn size(&self) -> usize {
+        match &self.statement {
+            Statement::Instruction(_, _, operands) => operands.len() + 1,
+            Statement::Data(values) => values.len(),
+            Statement::Empty | Statement::Constant(..) => 0,
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let valid = match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        _ => false,
+    };
+    valid && name != "rb"
+}
+
+fn parse_line(number: usize, source: &str) -> Result<Line<'_>, String> {
+    let mut source = source.split(';').next().unwrap().trim();
+    let mut label = None;
+
+    if let Some(index) = source.find(':') {
+        let name = source[..index].trim();
+        if !is_identifier(name) {
+            return Err(format!("Invalid label '{}'", name));
+        }
+        label = Some(name);
+        source = source[index + 1..].trim();
+    }
+
+    if source.is_empty() {
+        return Ok(Line { number, label, statement: Statement::Empty });
+    }
+
+    if let Some(index) = source.find('=') {
+        let name = source[..index].trim();
+        if label.is_some() || !is_identifier(name) {
+            return Err(format!("Invalid constant '{}'", source));
+        }
+        return Ok(Line { number, label, statement: Statement::Constant(name, source[index + 1..].trim()) });
+    }
+
+    let (mnemonic, operands) = match source.find(char::is_whitespace) {
+        Some(index) => (&source[..index], source[index..].trim()),
+        None => (source, ""),
+    };
+    let operands: Vec<&str> = if operands.is_empty() {
+        Vec::new()
+    } else {
+        operands.split(',').map(|operand| operand.trim()).collect()
+    };
+    let mnemonic = mnemonic.to_uppercase();
+
+    if mnemonic == "DATA" {
+        if operands.is_empty() {
+            return Err("DATA needs at least one value".to_string());
+        }
+        return Ok(Line { number, label, statement: Statement::Data(operands) });
+    }
+
+    match MNEMONICS.iter().find(|(name, ..)| *name == mnemonic) {
+        Some((_, opcode, count, destination)) if *count == operands.len() => {
+            Ok(Line { number, label, statement: Statement::Instruction(*opcode, *destination, operands) })
+        },
+        Some((name, _, count, _)) => Err(format!("{} takes {} operands, found {}", name, count, operands.len())),
+        None => Err(format!("Unknown mnemonic '{}'", mnemonic)),
+    }
+}
+
+fn evaluate(expression: &str, symbols: &HashMap<&str, Value>) -> Result<Value, String> {
+    let term = |term: &str| {
+        let term = term.trim();
+        if term.is_empty() {
+            Err(format!("Missing value in '{}'", expression))
+        } else if term.starts_with(|c: char| c.is_ascii_digit()) {
+            term.parse().map_err(|_| format!("Invalid number '{}'", term))
+        } else {
+            symbols.get(term).copied().ok_or_else(|| format!("Undefined symbol '{}'", term))
+        }
+    };
+
+    let mut total: Value = 0;
+    let mut rest = expression.trim();
+    loop {
+        let (negative, after) = match rest.chars().next() {
+            Some('-') => (true, &rest[1..]),
+            Some('+') => (false, &rest[1..]),
+            _ => (false, rest),
+        };
+        let end = after.find(|c| c == '+' || c == '-').unwrap_or(after.len());
+        let value = term(&after[..end])?;
+        total = if negative { total.checked_sub(value) } else { total.checked_add(value) }
+            .ok_or_else(|| format!("'{}' is out of range", expression))?;
+        rest = &after[end..];
+
+        if rest.is_empty() {
+            break Ok(total);
+        }
+    }
+}
+
+/// Evaluates an operand to its parameter mode and value.
+fn operand(operand: &str, symbols: &HashMap<&str, Value>) -> Result<(Value, Value), String> {
+    if operand.starts_with('#') {
+        return Ok((1, evaluate(&operand[1..], symbols)?));
+    }
+
+    if operand.starts_with('[') && operand.ends_with(']') {
+        let inner = operand[1..operand.len() - 1].trim();
+        if inner == "rb" {
+            return Ok((2, 0));
+        }
+        if inner.starts_with("rb") {
+            let offset = inner[2..].trim_start();
+            if offset.starts_with('+') || offset.starts_with('-') {
+                return Ok((2, evaluate(offset, symbols)?));
+            }
+        }
+        return Ok((0, evaluate(inner, symbols)?));
+    }
+
+    Err(format!("Operand '{}' must be #immediate, [position] or [rb+offset]", operand))
+}
+
+fn define<'a>(symbols: &mut HashMap<&'a str, Value>, line: &Line, name: &'a str, value: Value) -> Result<(), AssembleError> {
+    match symbols.insert(name, value) {
+        Some(_) => Err(AssembleError { line: line.number, message: format!("'{}' is already defined", name) }),
+        None => Ok(()),
+    }
+}
+
+/// Assembles source text into an Intcode program.
+///
+/// Each line holds an optional `label:` followed by an instruction such as `ADD #1, [x], [rb+2]`,
+/// a `DATA 1, 2, 3` directive or a `NAME = value` constant. Values can be numbers, labels or
+/// constants joined with `+` and `-`, and everything after a `;` is a comment.
+pub fn assemble(source: &str) -> Result<Vec<Value>, AssembleError> {
+    let lines = source.lines()
+        .enumerate()
+        .map(|(i, line)| parse_line(i + 1, line).map_err(|message| AssembleError { line: i + 1, message }))
+        .collect::<Result<Vec<Line>, AssembleError>>()?;
+
+    let mut symbols = HashMap::new();
+    let mut address = 0;
+    for line in &lines {
+        if let Some(label) = line.label {
+            define(&mut symbols, line, label, address as Value)?;
+        }
+        address += line.size();
+    }
+
+    for line in &lines {
+        if let Statement::Constant(name, expression) = line.statement {
+            let value = evaluate(expression, &symbols).map_err(|message| AssembleError { line: line.number, message })?;
+            define(&mut symbols, line, name, value)?;
+        }
+    }
+
+    let mut program = Vec::with_capacity(address);
+    for line in &lines {
+        let error = |message| AssembleError { line: line.number, message };
+        match &line.statement {
+            Statement::Instruction(opcode, destination, operands) => {
+                let mut word = *opcode;
+                let mut values = Vec::new();
+                for (i, text) in operands.iter().enumerate() {
+                    let (mode, value) = operand(text, &symbols).map_err(error)?;
+                    if mode == 1 && *destination == Some(i + 1) {
+                        return Err(error(format!("Operand '{}' is written to, so it can't be immediate", text)));
+                    }
+                    word += mode * Value::pow(10, i as u32 + 2);
+                    values.push(value);
+                }
+                program.push(word);
+                program.extend(values);
+            },
+            Statement::Data(values) => {
+                for value in values {
+                    program.push(evaluate(value, &symbols).map_err(error)?);
+                }
+            },
+            Statement::Empty | Statement::Constant(..) => (),
+        }
+    }
+
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::DOUBLER;
+
+    fn error(line: usize, message: &str) -> Result<Vec<Value>, AssembleError> {
+        Err(AssembleError { line, message: message.to_string() })
+    }
+
+    #[test]
+    fn test_assemble() {
+        let program = assemble("
+            ; Outputs each input doubled until it reads a zero
+            start:  in [x]
+                    jf [x], #done
+                    mul #2, [x], [x]
+                    out [x]
+                    jt #1, #start
+            done:   hlt
+            x:      data 0
+        ");
+
+        assert_eq!(program, Ok(DOUBLER.to_vec()));
+    }
+
+    #[test]
+    fn test_assemble_relative_and_constants() {
+        let program = assemble("
+            BASE = end + 10
+            ARB #BASE
+            ADD [rb], [rb + 1], [rb-2]
+            OUT [end - 1]
+            end:
+            DATA -1, BASE, 3
+        ");
+
+        assert_eq!(program, Ok(vec!(109, 18, 22201, 0, 1, -2, 4, 7, -1, 18, 3)));
+    }
+
+    #[test]
+    fn test_assemble_matches_disassembled_instructions() {
+        let program = vec!(21101, 5, -2, 3, 1206, -1, 7, 99);
+        let source: String = crate::disassemble(&program).lines().iter()
+            .map(|line| match line {
+                crate::Line::Instruction { instruction, .. } => format!("{}\n", instruction),
+                crate::Line::Data { value, .. } => format!("DATA {}\n", value),
+            })
+            .collect();
+
+        assert_eq!(assemble(&source), Ok(program));
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert_eq!(assemble("HLT\nJMP #0"), error(2, "Unknown mnemonic 'JMP'"));
+        assert_eq!(assemble("ADD #1, #2"), error(1, "ADD takes 3 operands, found 2"));
+        assert_eq!(assemble("\n\nIN #4"), error(3, "Operand '#4' is written to, so it can't be immediate"));
+        assert_eq!(assemble("OUT [missing]"), error(1, "Undefined symbol 'missing'"));
+        assert_eq!(assemble("a: HLT\na: HLT"), error(2, "'a' is already defined"));
+        assert_eq!(assemble("OUT 4"), error(1, "Operand '4' must be #immediate, [position] or [rb+offset]"));
+        assert_eq!(assemble("DATA 1, 2x"), error(1, "Invalid number '2x'"));
+        assert_eq!(assemble("1abel: HLT"), error(1, "Invalid label '1abel'"));
+        assert_eq!(assemble("OUT #1 +"), error(1, "Missing value in '1 +'"));
+        assert_eq!(
+            assemble("HLT\nDATA 9223372036854775807 + 1"),
+            error(2, "'9223372036854775807 + 1' is out of range")
+        );
+        assert_eq!(assemble("M = -9223372036854775807 - 1\nDATA 0 - M"), error(2, "'0 - M' is out of range"));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = AssembleError { line: 4, message: "Unknown mnemonic 'JMP'".to_string() };
+
+        assert_eq!(error.to_string(), "line 4: Unknown mnemonic 'JMP'");
+    }
+}
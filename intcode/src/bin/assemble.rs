@@ -0,0 +1,22 @@
+use std::env;
+use std::fs;
+use std::process;
+
+use intcode::assemble;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let input_file = args.get(1).expect("Input file required");
+    let source = fs::read_to_string(input_file).expect("Unable to read input file");
+
+    match assemble(&source) {
+        Ok(program) => {
+            let words: Vec<String> = program.iter().map(|word| word.to_string()).collect();
+            println!("{}", words.join(","));
+        },
+        Err(e) => {
+            eprintln!("Error in {}, {}", input_file, e);
+            process::exit(1);
+        },
+    }
+}
@@ -0,0 +1,33 @@
+use std::env;
+use std::fs;
+
+use intcode::{load_program, Coverage, IntcodeComputer, IterInput, Value};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let input_file = args.get(1).expect("Input file required");
+    let runs_file = args.get(2).expect("Runs file required");
+    let json = args.get(3).map(|arg| arg.as_str()) == Some("--json");
+    let program = load_program(input_file).expect("Unable to read input file");
+    let runs = fs::read_to_string(runs_file).expect("Unable to read runs file");
+
+    let mut coverage = Coverage::new(&program);
+    for (number, line) in runs.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let inputs: Vec<Value> = line.split(',')
+            .map(|x| x.trim().parse().expect("Invalid input in runs file"))
+            .collect();
+
+        let mut computer = IntcodeComputer::new(program.clone());
+        computer.set_profiling(true);
+        if let Err(e) = computer.run(&mut IterInput::new(inputs), &mut Vec::new()) {
+            eprintln!("Run on line {} failed: {}", number + 1, e);
+        }
+        coverage.add(computer.profile().unwrap());
+    }
+
+    if json {
+        println!("{}", coverage.to_json());
+    } else {
+        print!("{}", coverage);
+    }
+}
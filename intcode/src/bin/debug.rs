@@ -0,0 +1,28 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader};
+
+use intcode::{load_program, Debugger, IntcodeComputer};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let input_file = args.get(1).expect("Input file required");
+    let program = load_program(input_file).expect("Unable to read input file");
+
+    let mut debugger = Debugger::new(IntcodeComputer::new(program));
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    let result = match args.get(2) {
+        Some(script) => {
+            let script = File::open(script).expect("Unable to read script file");
+            debugger.run(BufReader::new(script), &mut out, true)
+        },
+        None => {
+            let stdin = io::stdin();
+            let commands = stdin.lock();
+            debugger.run(commands, &mut out, false)
+        },
+    };
+    result.expect("Unable to write to stdout");
+}
@@ -0,0 +1,11 @@
+use std::env;
+
+use intcode::{disassemble, load_program};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let input_file = args.get(1).expect("Input file required");
+    let program = load_program(input_file).expect("Unable to read input file");
+
+    print!("{}", disassemble(&program));
+}
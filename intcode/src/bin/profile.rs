@@ -0,0 +1,30 @@
+use std::env;
+use std::io;
+use std::process;
+
+use intcode::{disassemble, load_program, IntcodeComputer};
+
+const REPORT_LIMIT: usize = 20;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let input_file = args.get(1).expect("Input file required");
+    let listing_wanted = args.get(2).map(|arg| arg.as_str()) == Some("--listing");
+    let program = load_program(input_file).expect("Unable to read input file");
+    let listing = disassemble(&program);
+
+    let mut computer = IntcodeComputer::new(program);
+    computer.set_profiling(true);
+    let result = computer.run(&mut io::stdin(), &mut io::stdout());
+
+    let profile = computer.profile().unwrap();
+    eprint!("{}", profile.report(&listing, REPORT_LIMIT));
+    if listing_wanted {
+        eprint!("\n{}", profile.annotate(&listing));
+    }
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,23 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process;
+
+use intcode::{load_program, IntcodeComputer};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let input_file = args.get(1).expect("Input file required");
+    let trace_file = args.get(2).expect("Trace file required");
+    let program = load_program(input_file).expect("Unable to read input file");
+    let mut trace = BufWriter::new(File::create(trace_file).expect("Unable to create trace file"));
+
+    let mut computer = IntcodeComputer::new(program);
+    let result = computer.run_traced(&mut io::stdin(), &mut io::stdout(), &mut trace);
+    trace.flush().expect("Unable to write trace file");
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
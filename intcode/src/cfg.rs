@@ -0,0 +1,250 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::disassembler::{disassemble, Line};
+use crate::instruction::{Instruction, Parameter};
+use crate::Value;
+
+/// A run of instructions that is only ever entered at its first address.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BasicBlock {
+    pub start: usize,
+    /// The address just past the last instruction.
+    pub end: usize,
+    /// The instructions in the block with their addresses.
+    pub instructions: Vec<(usize, Instruction)>,
+    /// The block ends in a jump whose target comes from memory, so its successors are unknown.
+    pub computed_jump: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EdgeKind {
+    Jump,
+    FallThrough,
+}
+
+/// An edge between the blocks starting at `from` and `to`. `to` might not start a block if
+/// the program jumps into the middle of an instruction or into data.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ControlFlowGraph {
+    blocks: Vec<BasicBlock>,
+    edges: Vec<Edge>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl ControlFlowGraph {
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    pub fn block(&self, start: usize) -> Option<&BasicBlock> {
+        self.blocks.iter().find(|block| block.start == start)
+    }
+
+    /// Renders the graph in Graphviz DOT format. Blocks ending in a computed jump are red and
+    /// fall-through edges are dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph intcode {{").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+        for block in &self.blocks {
+            let label: String = block.instructions.iter()
+                .map(|(address, instruction)| format!("{}: {}\\l", address, escape(&instruction.to_string())))
+                .collect();
+            let colour = if block.computed_jump { ", color=red" } else { "" };
+            writeln!(dot, "    block_{} [label=\"{}\"{}];", block.start, label, colour).unwrap();
+        }
+
+        let unknown: BTreeSet<usize> = self.edges.iter()
+            .map(|edge| edge.to)
+            .filter(|&to| self.block(to).is_none())
+            .collect();
+        for address in unknown {
+            writeln!(dot, "    block_{} [label=\"{}: not code\", shape=plaintext];", address, address).unwrap();
+        }
+
+        for edge in &self.edges {
+            let style = match edge.kind {
+                EdgeKind::Jump => "",
+                EdgeKind::FallThrough => " [style=dashed]",
+            };
+            writeln!(dot, "    block_{} -> block_{}{};", edge.from, edge.to, style).unwrap();
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+/// Whether a jump with this condition and mode can be taken and can fall through.
+fn outcomes(instruction: &Instruction) -> (bool, bool) {
+    match instruction {
+        Instruction::JumpIfTrue(Parameter::Immediate(value), _) => (*value != 0, *value == 0),
+        Instruction::JumpIfFalse(Parameter::Immediate(value), _) => (*value == 0, *value != 0),
+        Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) => (true, true),
+        Instruction::Halt => (false, false),
+        _ => (false, true),
+    }
+}
+
+enum Target {
+    /// Not a jump, or a jump to a negative address that can only fail.
+    None,
+    Immediate(usize),
+    Computed,
+}
+
+fn target(instruction: &Instruction) -> Target {
+    match instruction {
+        Instruction::JumpIfTrue(_, target) | Instruction::JumpIfFalse(_, target) => match target {
+            Parameter::Immediate(target) if *target >= 0 => Target::Immediate(*target as usize),
+            Parameter::Immediate(_) => Target::None,
+            _ => Target::Computed,
+        },
+        _ => Target::None,
+    }
+}
+
+/// Splits the instructions found by `disassemble` into basic blocks and links them with
+/// the jumps that have immediate targets. Data words end a block and belong to none.
+pub fn control_flow_graph(program: &[Value]) -> ControlFlowGraph {
+    let listing = disassemble(program);
+    let instructions: Vec<(usize, usize, &Instruction)> = listing.lines().iter()
+        .filter_map(|line| match line {
+            Line::Instruction { address, words, instruction } => Some((*address, words.len(), instruction)),
+            Line::Data { .. } => None,
+        })
+        .collect();
+
+    let mut leaders = BTreeSet::new();
+    leaders.insert(0);
+    for &(address, size, instruction) in &instructions {
+        if let Target::Immediate(target) = target(instruction) {
+            leaders.insert(target);
+        }
+        if let Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) | Instruction::Halt = instruction {
+            leaders.insert(address + size);
+        }
+    }
+
+    let mut blocks: Vec<BasicBlock> = Vec::new();
+    for &(address, size, instruction) in &instructions {
+        match blocks.last_mut() {
+            Some(block) if block.end == address && !leaders.contains(&address) => {
+                block.end += size;
+                block.instructions.push((address, instruction.clone()));
+            },
+            _ => blocks.push(BasicBlock {
+                start: address,
+                end: address + size,
+                instructions: vec!((address, instruction.clone())),
+                computed_jump: false,
+            }),
+        }
+    }
+
+    let starts: BTreeSet<usize> = blocks.iter().map(|block| block.start).collect();
+    let mut edges = Vec::new();
+    for block in &mut blocks {
+        let last = &block.instructions.last().unwrap().1;
+        let (jumps, falls_through) = outcomes(last);
+
+        if jumps {
+            match target(last) {
+                Target::Immediate(target) => edges.push(Edge { from: block.start, to: target, kind: EdgeKind::Jump }),
+                Target::Computed => block.computed_jump = true,
+                Target::None => (),
+            }
+        }
+        if falls_through && starts.contains(&block.end) {
+            edges.push(Edge { from: block.start, to: block.end, kind: EdgeKind::FallThrough });
+        }
+    }
+
+    ControlFlowGraph { blocks, edges }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SUM;
+
+    #[test]
+    fn test_blocks_and_edges() {
+        let graph = control_flow_graph(&SUM);
+        let starts: Vec<(usize, usize)> = graph.blocks().iter().map(|block| (block.start, block.end)).collect();
+
+        assert_eq!(starts, vec!((0, 5), (5, 8), (8, 15)));
+        assert_eq!(graph.edges(), &[
+            Edge { from: 0, to: 8, kind: EdgeKind::Jump },
+            Edge { from: 0, to: 5, kind: EdgeKind::FallThrough },
+            Edge { from: 8, to: 0, kind: EdgeKind::Jump },
+        ]);
+        assert!(graph.blocks().iter().all(|block| !block.computed_jump));
+    }
+
+    #[test]
+    fn test_computed_jump() {
+        // JF #0, [5] always jumps to wherever [5] points, the HLT is never reached
+        let graph = control_flow_graph(&[106, 0, 5, 99, 104, 4]);
+
+        assert!(graph.block(0).unwrap().computed_jump);
+        assert_eq!(graph.edges(), &[]);
+    }
+
+    #[test]
+    fn test_jump_into_instruction() {
+        let graph = control_flow_graph(&[1105, 1, 4, 104, 5, 99]);
+
+        assert_eq!(graph.edges(), &[Edge { from: 0, to: 4, kind: EdgeKind::Jump }]);
+        assert_eq!(graph.block(4), None);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = control_flow_graph(&SUM).to_dot();
+
+        assert_eq!(dot, "\
+digraph intcode {
+    node [shape=box, fontname=\"monospace\"];
+    block_0 [label=\"0: IN   [16]\\l2: JT   [16], #8\\l\"];
+    block_5 [label=\"5: OUT  [15]\\l7: HLT\\l\"];
+    block_8 [label=\"8: ADD  [15], [16], [15]\\l12: JT   #1, #0\\l\"];
+    block_0 -> block_8;
+    block_0 -> block_5 [style=dashed];
+    block_8 -> block_0;
+}
+");
+    }
+
+    #[test]
+    fn test_to_dot_unknown_target() {
+        let dot = control_flow_graph(&[106, 0, 5, 1105, 1, 9, 99]).to_dot();
+
+        assert_eq!(dot, "\
+digraph intcode {
+    node [shape=box, fontname=\"monospace\"];
+    block_0 [label=\"0: JF   #0, [5]\\l\", color=red];
+    block_3 [label=\"3: JT   #1, #9\\l\"];
+    block_6 [label=\"6: HLT\\l\"];
+    block_9 [label=\"9: not code\", shape=plaintext];
+    block_3 -> block_9;
+}
+");
+    }
+}
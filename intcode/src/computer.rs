@@ -0,0 +1,1335 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+
+use crate::error::{ErrorKind, IntcodeError};
+use crate::instruction::{Instruction, Parameter};
+use crate::io::{Input, Output};
+use crate::profile::Profile;
+use crate::self_modification::{CodeTracker, CodeWrite, SelfModification};
+use crate::snapshot::Snapshot;
+use crate::trace::{MemoryWrite, Operand, StepRecord};
+use crate::watch::{self, WatchEvent, Watchpoint};
+use crate::Value;
+
+const DEFAULT_MEMORY_LIMIT: usize = 1 << 24;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Status {
+    /// The last instruction completed and the next one is ready to execute.
+    Running,
+    /// Execution is paused on an input instruction until `push_input` provides a value.
+    NeedsInput,
+    Output(Value),
+    Halted,
+    /// A halting watchpoint saw this access during the last instruction.
+    Watchpoint(WatchEvent),
+}
+
+/// What it takes to undo one executed instruction.
+#[derive(Debug, Clone)]
+struct Undo {
+    pc: usize,
+    relative_base: Value,
+    memory_size: usize,
+    writes: Vec<MemoryWrite>,
+    input: Option<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IntcodeComputer {
+    pc: usize,
+    program: Vec<Value>,
+    relative_base: Value,
+    inputs: VecDeque<Value>,
+    memory_limit: usize,
+    steps: u64,
+    step_limit: Option<u64>,
+    /// The hash of each state seen since the last input, with the step it was seen at.
+    seen_states: Option<HashMap<u64, u64>>,
+    recording: bool,
+    record: Option<StepRecord>,
+    watchpoints: Vec<Watchpoint>,
+    watch_hits: VecDeque<WatchEvent>,
+    watch_log: Vec<WatchEvent>,
+    history: Option<VecDeque<Undo>>,
+    history_limit: Option<usize>,
+    profile: Option<Profile>,
+    code_tracker: Option<CodeTracker>,
+    /// Whether the last step stopped on HLT, so stepping again doesn't count it twice.
+    halted: bool,
+}
+
+impl IntcodeComputer {
+    pub fn new(program: Vec<Value>) -> IntcodeComputer {
+        IntcodeComputer {
+            pc: 0,
+            program,
+            relative_base: 0,
+            inputs: VecDeque::new(),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            steps: 0,
+            step_limit: None,
+            seen_states: None,
+            recording: false,
+            record: None,
+            watchpoints: Vec::new(),
+            watch_hits: VecDeque::new(),
+            watch_log: Vec::new(),
+            history: None,
+            history_limit: None,
+            profile: None,
+            code_tracker: None,
+            halted: false,
+        }
+    }
+
+    /// Creates a computer in the state captured by `snapshot`. Limits and loop detection are
+    /// not part of the state and start with their defaults.
+    pub fn restore(snapshot: Snapshot) -> IntcodeComputer {
+        let mut computer = IntcodeComputer::new(snapshot.memory);
+        computer.pc = snapshot.pc;
+        computer.relative_base = snapshot.relative_base;
+        computer.steps = snapshot.steps;
+        computer.inputs = snapshot.inputs.into_iter().collect();
+        computer
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pc: self.pc,
+            relative_base: self.relative_base,
+            steps: self.steps,
+            inputs: self.inputs.iter().copied().collect(),
+            memory: self.program.clone(),
+        }
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn set_pc(&mut self, pc: usize) {
+        self.pc = pc;
+        self.halted = false;
+    }
+
+    pub fn relative_base(&self) -> Value {
+        self.relative_base
+    }
+
+    pub fn set_relative_base(&mut self, relative_base: Value) {
+        self.relative_base = relative_base;
+    }
+
+    /// Values pushed with `push_input` that the program hasn't read yet.
+    pub fn pending_inputs(&self) -> &VecDeque<Value> {
+        &self.inputs
+    }
+
+    pub fn program(&self) -> &[Value] {
+        &self.program
+    }
+
+    /// Sets how many memory cells the program may address before accesses fail.
+    pub fn set_memory_limit(&mut self, limit: usize) {
+        self.memory_limit = limit;
+    }
+
+    pub fn memory_limit(&self) -> usize {
+        self.memory_limit
+    }
+
+    /// The number of instructions executed so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Fails with `StepLimitExceeded` instead of executing more than `limit` instructions in total.
+    pub fn set_step_limit(&mut self, limit: Option<u64>) {
+        self.step_limit = limit;
+    }
+
+    /// Fails with `InfiniteLoop` if the pc, relative base and memory repeat without any input
+    /// being read in between. This hashes all of memory after every instruction, so it is slow.
+    /// A repeated hash is only reported once re-running from the state shows it really repeats.
+    pub fn set_loop_detection(&mut self, enabled: bool) {
+        self.seen_states = if enabled {
+            Some(vec!((self.state_hash(), self.steps)).into_iter().collect())
+        } else {
+            None
+        };
+    }
+
+    /// Keeps a `StepRecord` of the most recently executed instruction, see `last_step`.
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.recording = enabled;
+        if !enabled {
+            self.record = None;
+        }
+    }
+
+    /// What the most recently executed instruction did, if recording, watchpoints or history
+    /// were on when it ran.
+    pub fn last_step(&self) -> Option<&StepRecord> {
+        self.record.as_ref()
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) {
+        self.watchpoints.push(watchpoint);
+    }
+
+    /// Removes every watchpoint that covers `address`.
+    pub fn remove_watchpoints(&mut self, address: usize) {
+        self.watchpoints.retain(|watchpoint| address < watchpoint.start || watchpoint.end < address);
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    /// Accesses seen by logging watchpoints since the log was last taken.
+    pub fn take_watch_log(&mut self) -> Vec<WatchEvent> {
+        self.watch_log.drain(..).collect()
+    }
+
+    /// Keeps an undo log of every instruction executed from now on, so execution can be
+    /// wound back with `step_back`, `run_back_to_write` and `rewind_to`. The log grows by
+    /// one entry per instruction until history is turned off, unless it has a limit.
+    pub fn set_history(&mut self, enabled: bool) {
+        self.history = if enabled { Some(VecDeque::new()) } else { None };
+    }
+
+    /// Keeps only the most recent `limit` instructions in the undo log, forgetting older ones.
+    pub fn set_history_limit(&mut self, limit: Option<usize>) {
+        self.history_limit = limit;
+        self.trim_history();
+    }
+
+    fn trim_history(&mut self) {
+        if let (Some(history), Some(limit)) = (&mut self.history, self.history_limit) {
+            while history.len() > limit {
+                history.pop_front();
+            }
+        }
+    }
+
+    /// Undoes the last instruction executed while history was on, putting back any input it
+    /// read. Values already output can't be taken back. Returns false if there is nothing to undo.
+    pub fn step_back(&mut self) -> bool {
+        let undo = match self.history.as_mut().and_then(|history| history.pop_back()) {
+            Some(undo) => undo,
+            None => return false,
+        };
+
+        for write in undo.writes.iter().rev() {
+            self.store(write.address, write.old);
+        }
+        self.program.truncate(undo.memory_size);
+        self.pc = undo.pc;
+        self.relative_base = undo.relative_base;
+        if let Some(value) = undo.input {
+            self.inputs.push_front(value);
+        }
+        self.steps -= 1;
+
+        self.record = None;
+        self.watch_hits.clear();
+        // The states after this point haven't happened yet
+        if self.seen_states.is_some() {
+            self.set_loop_detection(true);
+        }
+        true
+    }
+
+    /// Steps back to just before the most recent instruction that wrote to `address`, and
+    /// returns its step number. Nothing changes if no recorded instruction wrote there.
+    pub fn run_back_to_write(&mut self, address: usize) -> Option<u64> {
+        let position = self.history.as_ref()?
+            .iter()
+            .rposition(|undo| undo.writes.iter().any(|write| write.address == address))?;
+
+        while self.history.as_ref().map_or(0, VecDeque::len) > position {
+            self.step_back();
+        }
+        Some(self.steps)
+    }
+
+    /// Steps back until `steps` instructions have been executed. Returns false, changing
+    /// nothing, if that is before history was turned on or hasn't happened yet.
+    pub fn rewind_to(&mut self, steps: u64) -> bool {
+        let recorded = self.history.as_ref().map_or(0, |history| history.len() as u64);
+        if steps > self.steps || steps < self.steps - recorded {
+            return false;
+        }
+
+        while self.steps > steps {
+            self.step_back();
+        }
+        true
+    }
+
+    /// Counts executed addresses, opcodes and backward jumps, starting from empty counts.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profile = if enabled { Some(Profile::default()) } else { None };
+    }
+
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Watches for writes to addresses that have been executed as part of an instruction
+    /// since this was called. With `None` nothing is tracked.
+    pub fn set_self_modification(&mut self, action: Option<SelfModification>) {
+        self.code_tracker = action.map(CodeTracker::new);
+    }
+
+    /// Writes to executed code seen while reporting self-modification.
+    pub fn code_writes(&self) -> &[CodeWrite] {
+        self.code_tracker.as_ref().map_or(&[], |tracker| tracker.writes())
+    }
+
+    pub fn push_input(&mut self, value: Value) {
+        self.inputs.push_back(value);
+    }
+
+    /// Reads a memory cell. Memory past the end of the program reads as zero.
+    pub fn get(&self, index: usize) -> Value {
+        self.program.get(index).copied().unwrap_or(0)
+    }
+
+    /// Writes a memory cell, growing memory with zeros if it is past the end of the program.
+    /// Fails with `AddressOutOfRange` at or past the memory limit.
+    pub fn set(&mut self, index: usize, value: Value) -> Result<(), IntcodeError> {
+        if index >= self.memory_limit {
+            return Err(IntcodeError::new(self.pc, ErrorKind::AddressOutOfRange(index as Value)));
+        }
+        self.store(index, value);
+        Ok(())
+    }
+
+    /// Writes a memory cell the caller has already checked against the memory limit.
+    fn store(&mut self, index: usize, value: Value) {
+        if index >= self.program.len() {
+            self.program.resize(index + 1, 0);
+        }
+        self.program[index] = value;
+    }
+
+    fn address(&self, address: Value) -> Result<usize, IntcodeError> {
+        if address < 0 {
+            Err(IntcodeError::new(self.pc, ErrorKind::InvalidAddress(address)))
+        } else if address as u64 >= self.memory_limit as u64 {
+            Err(IntcodeError::new(self.pc, ErrorKind::AddressOutOfRange(address)))
+        } else {
+            Ok(address as usize)
+        }
+    }
+
+    fn get_parameter_value(&self, parameter: &Parameter) -> Result<Value, IntcodeError> {
+        match parameter {
+            Parameter::Position(address) => Ok(self.get(self.address(*address)?)),
+            Parameter::Immediate(value) => Ok(*value),
+            Parameter::Relative(offset) => Ok(self.get(self.address(self.relative_base.saturating_add(*offset))?)),
+        }
+    }
+
+    /// Evaluates a parameter that is read from, noting it in the step record.
+    fn read(&mut self, parameter: &Parameter) -> Result<Value, IntcodeError> {
+        let value = self.get_parameter_value(parameter)?;
+        if self.record.is_none() {
+            return Ok(value);
+        }
+
+        let address = match parameter {
+            Parameter::Immediate(_) => None,
+            _ => Some(self.get_parameter_address(parameter)?),
+        };
+        if let Some(record) = &mut self.record {
+            record.operands.push(Operand { address, value });
+        }
+        Ok(value)
+    }
+
+    /// Stores `value` at the address a parameter refers to, noting it in the step record.
+    fn write(&mut self, parameter: &Parameter, value: Value) -> Result<(), IntcodeError> {
+        let address = self.get_parameter_address(parameter)?;
+        let old = self.get(address);
+        if let Some(tracker) = &mut self.code_tracker {
+            tracker.check(CodeWrite { step: self.steps, pc: self.pc, address, old, new: value })
+                .map_err(|kind| IntcodeError::new(self.pc, kind))?;
+        }
+        self.store(address, value);
+
+        if let Some(record) = &mut self.record {
+            record.operands.push(Operand { address: Some(address), value });
+            record.writes.push(MemoryWrite { address, old, new: value });
+        }
+        Ok(())
+    }
+
+    fn get_parameter_address(&self, parameter: &Parameter) -> Result<usize, IntcodeError> {
+        match parameter {
+            Parameter::Position(address) => self.address(*address),
+            Parameter::Relative(offset) => self.address(self.relative_base.saturating_add(*offset)),
+            Parameter::Immediate(_) => unreachable!("Instruction::parse rejects immediate write targets"),
+        }
+    }
+
+    /// Decodes the instruction at the pc. Unlike data, an instruction isn't zero-filled past the
+    /// end of memory, and fails with `TruncatedInstruction` if it doesn't fit.
+    pub fn next_instruction(&self) -> Result<Instruction, IntcodeError> {
+        let words = self.program.get(self.pc..).unwrap_or(&[]);
+        Instruction::parse(words).map_err(|kind| IntcodeError::new(self.pc, kind))
+    }
+
+    fn jump(&mut self, value: usize) {
+        self.pc = value;
+    }
+
+    fn jump_to(&mut self, target: Value) -> Result<(), IntcodeError> {
+        let target = self.address(target)?;
+        self.jump(target);
+        Ok(())
+    }
+
+    fn overflow(&self) -> IntcodeError {
+        IntcodeError::new(self.pc, ErrorKind::Overflow)
+    }
+
+    /// Jumps to `target` if the condition held, otherwise carries on to the next instruction.
+    fn branch(&mut self, taken: bool, target: &Parameter) -> Result<(), IntcodeError> {
+        if taken {
+            let target = self.read(target)?;
+            return self.jump_to(target);
+        }
+
+        // The target is never read, so it isn't recorded and can't fail
+        self.jump(self.pc + 3);
+        Ok(())
+    }
+
+    fn check_step_limit(&self) -> Result<(), IntcodeError> {
+        match self.step_limit {
+            Some(limit) if self.steps >= limit => {
+                Err(IntcodeError::new(self.pc, ErrorKind::StepLimitExceeded(limit)))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.pc, self.relative_base, &self.program).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn check_for_loop(&mut self) -> Result<(), IntcodeError> {
+        if self.seen_states.is_none() {
+            return Ok(());
+        }
+
+        let state = self.state_hash();
+        let steps = self.steps;
+        let seen_at = self.seen_states.as_mut().and_then(|seen_states| seen_states.insert(state, steps));
+        match seen_at {
+            Some(step) if self.repeats_after(self.steps - step) => {
+                Err(IntcodeError::new(self.pc, ErrorKind::InfiniteLoop))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether executing `steps` more instructions without reading input comes back to
+    /// exactly the current state. Runs on a copy, so nothing here changes.
+    fn repeats_after(&self, steps: u64) -> bool {
+        let mut copy = IntcodeComputer::new(self.program.clone());
+        copy.pc = self.pc;
+        copy.relative_base = self.relative_base;
+        copy.memory_limit = self.memory_limit;
+
+        for _ in 0..steps {
+            match copy.step() {
+                Ok(Status::Running) | Ok(Status::Output(_)) => (),
+                _ => return false,
+            }
+        }
+        (copy.pc, copy.relative_base, &copy.program) == (self.pc, self.relative_base, &self.program)
+    }
+
+    /// A record for `instruction` if anything needs one.
+    fn new_record(&self, instruction: &Instruction) -> Option<StepRecord> {
+        if self.recording || !self.watchpoints.is_empty() || self.history.is_some() {
+            Some(StepRecord::new(self.steps, self.pc, self.relative_base, instruction.clone()))
+        } else {
+            None
+        }
+    }
+
+    fn save_undo(&mut self, memory_size: usize) {
+        if let (Some(history), Some(record)) = (&mut self.history, &self.record) {
+            history.push_back(Undo {
+                pc: record.pc,
+                relative_base: record.relative_base,
+                memory_size,
+                writes: record.writes.clone(),
+                input: record.input,
+            });
+        }
+        self.trim_history();
+    }
+
+    fn check_watchpoints(&mut self) {
+        if let (false, Some(record)) = (self.watchpoints.is_empty(), &self.record) {
+            let (halts, logs) = watch::check(&self.watchpoints, record);
+            self.watch_hits.extend(halts);
+            self.watch_log.extend(logs);
+        }
+    }
+
+    /// Executes a single instruction.
+    ///
+    /// When a halting watchpoint fires, the instruction's status is replaced with
+    /// `Status::Watchpoint`, unless it produced an output. Any further events from the same
+    /// instruction are returned by the following calls, before anything else is executed.
+    pub fn step(&mut self) -> Result<Status, IntcodeError> {
+        if let Some(event) = self.watch_hits.pop_front() {
+            return Ok(Status::Watchpoint(event));
+        }
+
+        let instruction = self.next_instruction()?;
+        let halted = std::mem::replace(&mut self.halted, false);
+        match instruction {
+            Instruction::Halt => {
+                if !halted {
+                    self.record = self.new_record(&instruction);
+                    if let Some(profile) = &mut self.profile {
+                        profile.record(self.pc, &instruction, self.pc, false);
+                    }
+                }
+                self.halted = true;
+                return Ok(Status::Halted);
+            },
+            Instruction::Input(_) if self.inputs.is_empty() => return Ok(Status::NeedsInput),
+            _ => self.check_step_limit()?,
+        }
+
+        self.record = self.new_record(&instruction);
+
+        if let Some(tracker) = &mut self.code_tracker {
+            tracker.executed(self.pc, instruction.size());
+        }
+
+        let (pc, memory_size) = (self.pc, self.program.len());
+        let profiled = if self.profile.is_some() { Some(instruction.clone()) } else { None };
+        let (status, jumped) = self.execute(instruction)?;
+        self.steps += 1;
+        if let (Some(profile), Some(instruction)) = (&mut self.profile, &profiled) {
+            profile.record(pc, instruction, self.pc, jumped);
+        }
+        self.save_undo(memory_size);
+        self.check_for_loop()?;
+        self.check_watchpoints();
+
+        match status {
+            Status::Running => Ok(self.watch_hits.pop_front().map_or(Status::Running, Status::Watchpoint)),
+            status => Ok(status),
+        }
+    }
+
+    /// Returns the instruction's status, and whether it was a conditional jump that jumped.
+    fn execute(&mut self, instruction: Instruction) -> Result<(Status, bool), IntcodeError> {
+        match instruction {
+            Instruction::Add(a, b, result) => {
+                let value = self.read(&a)?.checked_add(self.read(&b)?).ok_or_else(|| self.overflow())?;
+                self.write(&result, value)?;
+                self.jump(self.pc + 4);
+            }
+            Instruction::Multiply(a, b, result) => {
+                let value = self.read(&a)?.checked_mul(self.read(&b)?).ok_or_else(|| self.overflow())?;
+                self.write(&result, value)?;
+                self.jump(self.pc + 4);
+            },
+            Instruction::Input(location) => {
+                // The value stays queued if the write fails
+                let value = *self.inputs.front().ok_or_else(|| IntcodeError::new(self.pc, ErrorKind::NoInput))?;
+                self.write(&location, value)?;
+                self.inputs.pop_front();
+                self.jump(self.pc + 2);
+
+                if let Some(record) = &mut self.record {
+                    record.input = Some(value);
+                }
+
+                // States before an input can't repeat forever, the next input may differ
+                if let Some(seen_states) = &mut self.seen_states {
+                    seen_states.clear();
+                }
+            },
+            Instruction::Output(i) => {
+                let value = self.read(&i)?;
+                self.jump(self.pc + 2);
+
+                if let Some(record) = &mut self.record {
+                    record.output = Some(value);
+                }
+                return Ok((Status::Output(value), false));
+            },
+            Instruction::JumpIfTrue(value, target) => {
+                let condition = self.read(&value)? != 0;
+                self.branch(condition, &target)?;
+                return Ok((Status::Running, condition));
+            },
+            Instruction::JumpIfFalse(value, target) => {
+                let condition = self.read(&value)? == 0;
+                self.branch(condition, &target)?;
+                return Ok((Status::Running, condition));
+            },
+            Instruction::LessThan(a, b, result) => {
+                let value = if self.read(&a)? < self.read(&b)? {
+                    1
+                } else {
+                    0
+                };
+
+                self.write(&result, value)?;
+                self.jump(self.pc + 4);
+            },
+            Instruction::Equals(a, b, result) => {
+                let value = if self.read(&a)? == self.read(&b)? {
+                    1
+                } else {
+                    0
+                };
+
+                self.write(&result, value)?;
+                self.jump(self.pc + 4);
+            }
+            Instruction::AdjustRelativeBase(offset) => {
+                let offset = self.read(&offset)?;
+                self.relative_base = self.relative_base.checked_add(offset).ok_or_else(|| self.overflow())?;
+                self.jump(self.pc + 2);
+            },
+            Instruction::Halt => return Ok((Status::Halted, false)),
+        }
+
+        Ok((Status::Running, false))
+    }
+
+    /// Runs until the program outputs a value, needs more input than has been pushed, or halts.
+    pub fn resume(&mut self) -> Result<Status, IntcodeError> {
+        loop {
+            match self.step()? {
+                Status::Running => continue,
+                status => break Ok(status),
+            }
+        }
+    }
+
+    /// Runs the program to completion. Halting watchpoints are ignored, use `resume` to stop
+    /// at them.
+    pub fn run<I, O>(&mut self, input: &mut I, output: &mut O) -> Result<(), IntcodeError>
+    where
+        I: Input + ?Sized,
+        O: Output + ?Sized,
+    {
+        loop {
+            match self.resume()? {
+                Status::NeedsInput => {
+                    let value = input.read().ok_or_else(|| IntcodeError::new(self.pc, ErrorKind::NoInput))?;
+                    self.push_input(value);
+                },
+                Status::Output(value) => output.write(value),
+                Status::Halted => break Ok(()),
+                Status::Watchpoint(_) => (),
+                Status::Running => unreachable!(),
+            }
+        }
+    }
+
+    /// Runs like `run`, also writing a JSON Lines record of every executed instruction to
+    /// `trace`, ending with the HLT. This turns recording on.
+    pub fn run_traced<I, O, W>(&mut self, input: &mut I, output: &mut O, trace: &mut W) -> Result<(), IntcodeError>
+    where
+        I: Input + ?Sized,
+        O: Output + ?Sized,
+        W: Write + ?Sized,
+    {
+        self.set_recording(true);
+        loop {
+            let (steps, halted) = (self.steps, self.halted);
+            let status = self.step()?;
+            if self.steps > steps || (status == Status::Halted && !halted) {
+                let record = self.record.as_ref().expect("recording is on");
+                writeln!(trace, "{}", record.to_json())
+                    .map_err(|e| IntcodeError::new(record.pc, ErrorKind::TraceFailed(e.to_string())))?;
+            }
+
+            match status {
+                Status::NeedsInput => {
+                    let value = input.read().ok_or_else(|| IntcodeError::new(self.pc, ErrorKind::NoInput))?;
+                    self.push_input(value);
+                },
+                Status::Output(value) => output.write(value),
+                Status::Halted => break Ok(()),
+                Status::Running | Status::Watchpoint(_) => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use crate::fixtures::{DOUBLER, SUM};
+    use crate::io::IterInput;
+    use crate::profile::HotLoop;
+    use crate::watch::{Access, WatchAction};
+
+    #[test]
+    fn test_get_parameter_value_position() {
+        let computer = IntcodeComputer::new(vec!(12, 2, 3, 4));
+        let value = computer.get_parameter_value(&Parameter::Position(2)).unwrap();
+
+        assert_eq!(value, 3);
+    }
+
+    #[test]
+    fn test_get_parameter_value_immediate() {
+        let computer = IntcodeComputer::new(vec!(12, 2, 3, 4));
+        let value = computer.get_parameter_value(&Parameter::Immediate(2)).unwrap();
+
+        assert_eq!(value, 2);
+    }
+
+    #[test]
+    fn test_get_parameter_value_relative() {
+        let mut computer = IntcodeComputer::new(vec!(12, 2, 3, 4));
+        computer.relative_base = 3;
+        let value = computer.get_parameter_value(&Parameter::Relative(-2)).unwrap();
+
+        assert_eq!(value, 2);
+    }
+
+    #[test]
+    fn test_get_parameter_address() {
+        let mut computer = IntcodeComputer::new(vec!(12, 2, 3, 4));
+        computer.relative_base = 3;
+
+        assert_eq!(computer.get_parameter_address(&Parameter::Position(1)), Ok(1));
+        assert_eq!(computer.get_parameter_address(&Parameter::Relative(-2)), Ok(1));
+        assert_eq!(
+            computer.get_parameter_address(&Parameter::Relative(-4)),
+            Err(IntcodeError::new(0, ErrorKind::InvalidAddress(-1)))
+        );
+    }
+
+    #[test]
+    fn test_next_instruction() {
+        let mut computer = IntcodeComputer::new(vec!(12, 102, 3, 4, 5));
+        computer.pc = 1;
+        let instruction = computer.next_instruction().unwrap();
+
+        assert_eq!(instruction, Instruction::Multiply(
+            Parameter::Immediate(3),
+            Parameter::Position(4),
+            Parameter::Position(5)
+        ));
+    }
+
+    #[test]
+    fn test_run() {
+        let mut computer = IntcodeComputer::new(vec!(101, 10, 6, 0, 1002, 0, 20, 4, 99));
+        computer.run(&mut VecDeque::new(), &mut Vec::new()).unwrap();
+
+        assert_eq!(computer.program, vec!(30, 10, 6, 0, 600, 0, 20, 4, 99));
+    }
+
+    #[test]
+    fn test_run_comparisons_and_jumps() {
+        // Stores (1 == 1) at 13, then jumps over the write to 14 when it is true
+        let mut computer = IntcodeComputer::new(vec!(1108, 1, 1, 13, 1005, 13, 11, 1101, 1, 1, 14, 99, 0, 0, 0));
+        computer.run(&mut VecDeque::new(), &mut Vec::new()).unwrap();
+
+        assert_eq!(computer.get(13), 1);
+        assert_eq!(computer.get(14), 0);
+        assert_eq!(computer.pc(), 11);
+    }
+
+    #[test]
+    fn test_run_input_output() {
+        let mut computer = IntcodeComputer::new(vec!(3, 9, 1002, 9, 3, 9, 4, 9, 99, 0));
+        let mut output = Vec::new();
+        computer.run(&mut IterInput::new(vec!(7)), &mut output).unwrap();
+
+        assert_eq!(output, vec!(21));
+    }
+
+    #[test]
+    fn test_run_input_exhausted() {
+        let mut computer = IntcodeComputer::new(vec!(3, 0, 99));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::NoInput)));
+        assert_eq!(computer.pc(), 0);
+    }
+
+    #[test]
+    fn test_run_closure_output() {
+        let mut computer = IntcodeComputer::new(vec!(104, 5, 104, 6, 99));
+        let mut total = 0;
+        computer.run(&mut VecDeque::new(), &mut |value| total += value).unwrap();
+
+        assert_eq!(total, 11);
+    }
+
+    #[test]
+    fn test_step() {
+        let mut computer = IntcodeComputer::new(vec!(1101, 1, 2, 0, 99));
+
+        assert_eq!(computer.step(), Ok(Status::Running));
+        assert_eq!(computer.get(0), 3);
+        assert_eq!(computer.pc(), 4);
+        assert_eq!(computer.step(), Ok(Status::Halted));
+        assert_eq!(computer.pc(), 4);
+    }
+
+    #[test]
+    fn test_resume() {
+        let mut computer = IntcodeComputer::new(DOUBLER.to_vec());
+
+        assert_eq!(computer.resume(), Ok(Status::NeedsInput));
+        assert_eq!(computer.pc(), 0);
+
+        computer.push_input(4);
+        assert_eq!(computer.resume(), Ok(Status::Output(8)));
+        assert_eq!(computer.resume(), Ok(Status::NeedsInput));
+
+        computer.push_input(5);
+        computer.push_input(0);
+        assert_eq!(computer.resume(), Ok(Status::Output(10)));
+        assert_eq!(computer.resume(), Ok(Status::Halted));
+        assert_eq!(computer.resume(), Ok(Status::Halted));
+    }
+
+    #[test]
+    fn test_run_relative_base() {
+        // Moves the relative base to 11, reads into rb+0 and outputs rb+0 + rb+1 via rb+2
+        let mut computer = IntcodeComputer::new(vec!(109, 11, 203, 0, 22201, 0, 1, 2, 204, 2, 99, 0, 5, 0));
+        let mut output = Vec::new();
+        computer.run(&mut IterInput::new(vec!(7)), &mut output).unwrap();
+
+        assert_eq!(computer.relative_base(), 11);
+        assert_eq!(computer.get(11), 7);
+        assert_eq!(output, vec!(7 + 5));
+    }
+
+    #[test]
+    fn test_memory_grows() {
+        let mut computer = IntcodeComputer::new(vec!(1, 2));
+
+        assert_eq!(computer.get(1000), 0);
+        assert_eq!(computer.program().len(), 2);
+
+        computer.set(5, 3).unwrap();
+        assert_eq!(computer.program(), &[1, 2, 0, 0, 0, 3]);
+
+        computer.set_memory_limit(6);
+        assert_eq!(computer.set(6, 3), Err(IntcodeError::new(0, ErrorKind::AddressOutOfRange(6))));
+        assert_eq!(computer.program().len(), 6);
+    }
+
+    #[test]
+    fn test_run_large_numbers() {
+        let mut computer = IntcodeComputer::new(vec!(1102, 34915192, 34915192, 7, 4, 7, 99, 0));
+        let mut output = Vec::new();
+        computer.run(&mut VecDeque::new(), &mut output).unwrap();
+
+        assert_eq!(output, vec!(1219070632396864));
+    }
+
+    #[test]
+    fn test_run_overflow() {
+        let overflows = |program: Vec<Value>, pc| {
+            let mut computer = IntcodeComputer::new(program);
+            let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+            assert_eq!(result, Err(IntcodeError::new(pc, ErrorKind::Overflow)));
+        };
+
+        overflows(vec!(1101, std::i64::MAX, 1, 0, 99), 0);
+        overflows(vec!(1101, std::i64::MIN, -1, 0, 99), 0);
+        overflows(vec!(1102, std::i64::MAX, 2, 0, 99), 0);
+        overflows(vec!(109, std::i64::MAX, 109, 1, 99), 2);
+    }
+
+    #[test]
+    fn test_run_quine() {
+        // Relative mode example from day 9 that outputs a copy of itself using memory past its end
+        let program = vec!(109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99);
+        let mut computer = IntcodeComputer::new(program.clone());
+        let mut output = Vec::new();
+        computer.run(&mut VecDeque::new(), &mut output).unwrap();
+
+        assert_eq!(output, program);
+    }
+
+    #[test]
+    fn test_run_unknown_opcode() {
+        let mut computer = IntcodeComputer::new(vec!(1101, 20, 3, 4, 99));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(4, ErrorKind::UnknownOpcode(23))));
+    }
+
+    #[test]
+    fn test_run_truncated_instruction() {
+        let mut computer = IntcodeComputer::new(vec!(1101, 1));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::TruncatedInstruction)));
+
+        // Running off the end of memory
+        let mut computer = IntcodeComputer::new(vec!(1101, 1, 1, 0));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(4, ErrorKind::TruncatedInstruction)));
+    }
+
+    #[test]
+    fn test_run_negative_jump() {
+        let mut computer = IntcodeComputer::new(vec!(1105, 1, -3));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::InvalidAddress(-3))));
+    }
+
+    #[test]
+    fn test_run_immediate_write_target() {
+        let mut computer = IntcodeComputer::new(vec!(11101, 1, 1, 0, 99));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::InvalidParameterMode {
+            opcode: 11101,
+            parameter: 3,
+            mode: 1,
+        })));
+        assert_eq!(computer.get(0), 11101);
+    }
+
+    #[test]
+    fn test_run_negative_read() {
+        let mut computer = IntcodeComputer::new(vec!(4, -1, 99));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::InvalidAddress(-1))));
+    }
+
+    #[test]
+    fn test_run_negative_relative_write() {
+        let mut computer = IntcodeComputer::new(vec!(109, -5, 21101, 1, 1, 0, 99));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(2, ErrorKind::InvalidAddress(-5))));
+    }
+
+    #[test]
+    fn test_run_address_out_of_range() {
+        let mut computer = IntcodeComputer::new(vec!(1101, 1, 1, 100, 99));
+        computer.set_memory_limit(100);
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::AddressOutOfRange(100))));
+        assert_eq!(computer.program().len(), 5);
+    }
+
+    #[test]
+    fn test_run_huge_address() {
+        let mut computer = IntcodeComputer::new(vec!(4, std::i64::MAX, 99));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::AddressOutOfRange(std::i64::MAX))));
+    }
+
+    #[test]
+    fn test_failed_input_keeps_value() {
+        let mut computer = IntcodeComputer::new(vec!(3, -1, 99));
+        computer.push_input(5);
+
+        assert_eq!(computer.step(), Err(IntcodeError::new(0, ErrorKind::InvalidAddress(-1))));
+        assert_eq!(computer.pending_inputs(), &[5]);
+
+        let mut computer = IntcodeComputer::new(vec!(3, 0, 99));
+        computer.set_self_modification(Some(SelfModification::Fail));
+        computer.push_input(5);
+
+        assert_eq!(computer.step(), Err(IntcodeError::new(0, ErrorKind::SelfModifyingCode(0))));
+        assert_eq!(computer.pending_inputs(), &[5]);
+    }
+
+    #[test]
+    fn test_steps() {
+        let mut computer = IntcodeComputer::new(vec!(1101, 1, 1, 0, 104, 5, 99));
+        computer.run(&mut VecDeque::new(), &mut Vec::new()).unwrap();
+
+        assert_eq!(computer.steps(), 2);
+    }
+
+    #[test]
+    fn test_run_step_limit() {
+        let mut computer = IntcodeComputer::new(vec!(1105, 1, 0));
+        computer.set_step_limit(Some(10));
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::StepLimitExceeded(10))));
+        assert_eq!(computer.steps(), 10);
+    }
+
+    #[test]
+    fn test_run_step_limit_not_reached() {
+        let mut computer = IntcodeComputer::new(vec!(1101, 1, 1, 0, 99));
+        computer.set_step_limit(Some(1));
+
+        assert_eq!(computer.run(&mut VecDeque::new(), &mut Vec::new()), Ok(()));
+    }
+
+    #[test]
+    fn test_run_detects_infinite_loop() {
+        // Counts 0, 1, 0, 1... at address 9 forever
+        let mut computer = IntcodeComputer::new(vec!(1008, 9, 0, 9, 1105, 1, 0, 99, 0, 0));
+        computer.set_loop_detection(true);
+        let result = computer.run(&mut VecDeque::new(), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::InfiniteLoop)));
+        assert_eq!(computer.steps(), 4);
+    }
+
+    #[test]
+    fn test_repeats_after() {
+        // Counts up in address 7 forever, so no state ever repeats
+        let mut counter = IntcodeComputer::new(vec!(1001, 7, 1, 7, 1105, 1, 0, 0));
+        counter.step().unwrap();
+        assert!(!counter.repeats_after(2));
+
+        let mut spinner = IntcodeComputer::new(vec!(1105, 1, 0));
+        spinner.step().unwrap();
+        assert!(spinner.repeats_after(1));
+
+        // Coming back round needs input, which could be different next time
+        let mut reader = IntcodeComputer::new(vec!(3, 5, 1105, 1, 0, 0));
+        reader.push_input(1);
+        assert!(!reader.repeats_after(2));
+    }
+
+    #[test]
+    fn test_run_loop_detection_resets_on_input() {
+        // Reads into address 5 forever
+        let mut computer = IntcodeComputer::new(vec!(3, 5, 1105, 1, 0, 0));
+        computer.set_loop_detection(true);
+        for _ in 0..3 {
+            computer.push_input(1);
+        }
+
+        assert_eq!(computer.resume(), Ok(Status::NeedsInput));
+        assert_eq!(computer.resume(), Ok(Status::NeedsInput));
+        assert_eq!(computer.steps(), 6);
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut computer = IntcodeComputer::new(DOUBLER.to_vec());
+        computer.push_input(4);
+        computer.push_input(5);
+        assert_eq!(computer.resume(), Ok(Status::Output(8)));
+
+        let snapshot = computer.snapshot();
+        assert_eq!(snapshot.pc, 11);
+        assert_eq!(snapshot.inputs, vec!(5));
+
+        let mut restored = IntcodeComputer::restore(snapshot.to_string().parse().unwrap());
+        assert_eq!(restored.steps(), computer.steps());
+        assert_eq!(restored.resume(), Ok(Status::Output(10)));
+        assert_eq!(computer.resume(), Ok(Status::Output(10)));
+        assert_eq!(restored.snapshot(), computer.snapshot());
+    }
+
+    #[test]
+    fn test_last_step() {
+        let mut computer = IntcodeComputer::new(vec!(1101, 5, -2, 7, 1005, 7, 0, 0));
+        computer.step().unwrap();
+        assert_eq!(computer.last_step(), None);
+
+        computer.set_recording(true);
+        computer.set_pc(0);
+        computer.step().unwrap();
+        assert_eq!(computer.last_step(), Some(&StepRecord {
+            step: 1,
+            pc: 0,
+            relative_base: 0,
+            instruction: Instruction::Add(Parameter::Immediate(5), Parameter::Immediate(-2), Parameter::Position(7)),
+            operands: vec!(
+                Operand { address: None, value: 5 },
+                Operand { address: None, value: -2 },
+                Operand { address: Some(7), value: 3 },
+            ),
+            writes: vec!(MemoryWrite { address: 7, old: 3, new: 3 }),
+            input: None,
+            output: None,
+        }));
+
+        computer.step().unwrap();
+        let record = computer.last_step().unwrap();
+        assert_eq!(record.operands, vec!(Operand { address: Some(7), value: 3 }, Operand { address: None, value: 0 }));
+        assert_eq!(record.writes, vec!());
+
+        computer.set_recording(false);
+        assert_eq!(computer.last_step(), None);
+    }
+
+    #[test]
+    fn test_record_jump_not_taken() {
+        let mut computer = IntcodeComputer::new(vec!(1106, 1, 7, 1006, 7, -1, 99, 5));
+        computer.set_recording(true);
+
+        assert_eq!(computer.step(), Ok(Status::Running));
+        assert_eq!(computer.last_step().unwrap().operands, vec!(Operand { address: None, value: 1 }));
+
+        // The target isn't read, so it doesn't matter that it can't be resolved
+        assert_eq!(computer.step(), Ok(Status::Running));
+        assert_eq!(computer.last_step().unwrap().operands, vec!(Operand { address: Some(7), value: 5 }));
+    }
+
+    #[test]
+    fn test_run_traced() {
+        let mut computer = IntcodeComputer::new(DOUBLER.to_vec());
+        let mut output = Vec::new();
+        let mut trace = Vec::new();
+        computer.run_traced(&mut IterInput::new(vec!(3, 0)), &mut output, &mut trace).unwrap();
+
+        assert_eq!(output, vec!(6));
+        assert_eq!(String::from_utf8(trace).unwrap(), r#"{"step":0,"pc":0,"relative_base":0,"instruction":"IN   [15]","operands":[{"mode":"position","address":15,"value":3}],"writes":[{"address":15,"old":0,"new":3}],"input":3}
+{"step":1,"pc":2,"relative_base":0,"instruction":"JF   [15], #14","operands":[{"mode":"position","address":15,"value":3}],"writes":[]}
+{"step":2,"pc":5,"relative_base":0,"instruction":"MUL  #2, [15], [15]","operands":[{"mode":"immediate","value":2},{"mode":"position","address":15,"value":3},{"mode":"position","address":15,"value":6}],"writes":[{"address":15,"old":3,"new":6}]}
+{"step":3,"pc":9,"relative_base":0,"instruction":"OUT  [15]","operands":[{"mode":"position","address":15,"value":6}],"writes":[],"output":6}
+{"step":4,"pc":11,"relative_base":0,"instruction":"JT   #1, #0","operands":[{"mode":"immediate","value":1},{"mode":"immediate","value":0}],"writes":[]}
+{"step":5,"pc":0,"relative_base":0,"instruction":"IN   [15]","operands":[{"mode":"position","address":15,"value":0}],"writes":[{"address":15,"old":6,"new":0}],"input":0}
+{"step":6,"pc":2,"relative_base":0,"instruction":"JF   [15], #14","operands":[{"mode":"position","address":15,"value":0},{"mode":"immediate","value":14}],"writes":[]}
+{"step":7,"pc":14,"relative_base":0,"instruction":"HLT","operands":[],"writes":[]}
+"#);
+    }
+
+    #[test]
+    fn test_jump_not_taken_doesnt_read_target() {
+        let mut computer = IntcodeComputer::new(vec!(6, 5, 6, 99, 0, 1, 42));
+        computer.add_watchpoint(Watchpoint { start: 6, end: 6, read: true, write: false, action: WatchAction::Halt });
+
+        assert_eq!(computer.resume(), Ok(Status::Halted));
+    }
+
+    #[test]
+    fn test_watchpoint_halts_on_write() {
+        let mut computer = IntcodeComputer::new(DOUBLER.to_vec());
+        computer.add_watchpoint(Watchpoint { start: 15, end: 15, read: false, write: true, action: WatchAction::Halt });
+        computer.push_input(3);
+
+        let write = |step, pc, old, new| {
+            Status::Watchpoint(WatchEvent { step, pc, address: 15, access: Access::Write, old, new })
+        };
+        assert_eq!(computer.resume(), Ok(write(0, 0, 0, 3)));
+        assert_eq!(computer.resume(), Ok(write(2, 5, 3, 6)));
+        assert_eq!(computer.resume(), Ok(Status::Output(6)));
+        assert_eq!(computer.resume(), Ok(Status::NeedsInput));
+    }
+
+    #[test]
+    fn test_watchpoint_after_output() {
+        let mut computer = IntcodeComputer::new(vec!(4, 3, 99, 42));
+        computer.add_watchpoint(Watchpoint { start: 0, end: 5, read: true, write: false, action: WatchAction::Halt });
+
+        assert_eq!(computer.step(), Ok(Status::Output(42)));
+        assert_eq!(
+            computer.step(),
+            Ok(Status::Watchpoint(WatchEvent { step: 0, pc: 0, address: 3, access: Access::Read, old: 42, new: 42 }))
+        );
+        assert_eq!(computer.step(), Ok(Status::Halted));
+    }
+
+    #[test]
+    fn test_last_step_cleared_without_watchpoints() {
+        let mut computer = IntcodeComputer::new(vec!(1101, 1, 1, 9, 104, 3, 99, 0, 0, 0));
+        computer.add_watchpoint(Watchpoint { start: 9, end: 9, read: false, write: true, action: WatchAction::Log });
+
+        assert_eq!(computer.step(), Ok(Status::Running));
+        assert_eq!(computer.last_step().map(|record| record.pc), Some(0));
+
+        computer.remove_watchpoints(9);
+        assert_eq!(computer.step(), Ok(Status::Output(3)));
+        assert_eq!(computer.last_step(), None);
+    }
+
+    #[test]
+    fn test_watchpoint_log() {
+        let mut computer = IntcodeComputer::new(SUM.to_vec());
+        computer.add_watchpoint(Watchpoint { start: 15, end: 15, read: false, write: true, action: WatchAction::Log });
+        computer.add_watchpoint(Watchpoint { start: 14, end: 16, read: true, write: false, action: WatchAction::Log });
+        computer.remove_watchpoints(16);
+
+        let mut output = Vec::new();
+        computer.run(&mut IterInput::new(vec!(4, 5, 0)), &mut output).unwrap();
+
+        assert_eq!(output, vec!(9));
+        assert_eq!(computer.watchpoints().len(), 1);
+        let log: Vec<String> = computer.take_watch_log().iter().map(|event| event.to_string()).collect();
+        assert_eq!(log, vec!("wrote 4 to 15 at pc 8, was 0", "wrote 9 to 15 at pc 8, was 4"));
+        assert_eq!(computer.take_watch_log(), vec!());
+    }
+
+    #[test]
+    fn test_step_back() {
+        let mut computer = IntcodeComputer::new(DOUBLER.to_vec());
+        computer.set_history(true);
+        computer.push_input(3);
+        let start = computer.snapshot();
+
+        assert_eq!(computer.resume(), Ok(Status::Output(6)));
+        let output = computer.snapshot();
+        assert_eq!(computer.resume(), Ok(Status::NeedsInput));
+
+        assert!(computer.step_back());
+        assert_eq!(computer.snapshot(), output);
+
+        assert!(computer.rewind_to(0));
+        assert_eq!(computer.snapshot(), start);
+        assert!(!computer.step_back());
+        assert_eq!(computer.resume(), Ok(Status::Output(6)));
+    }
+
+    #[test]
+    fn test_step_back_shrinks_memory() {
+        let mut computer = IntcodeComputer::new(vec!(21101, 1, 2, 10, 99));
+        computer.set_history(true);
+        computer.set_relative_base(-4);
+        computer.step().unwrap();
+        assert_eq!(computer.program().len(), 7);
+
+        assert!(computer.step_back());
+        assert_eq!(computer.program(), &[21101, 1, 2, 10, 99]);
+        assert_eq!(computer.relative_base(), -4);
+    }
+
+    #[test]
+    fn test_run_back_to_write() {
+        let mut computer = IntcodeComputer::new(SUM.to_vec());
+        computer.set_history(true);
+        computer.run(&mut IterInput::new(vec!(4, 5, 0)), &mut Vec::new()).unwrap();
+        assert_eq!(computer.steps(), 11);
+
+        assert_eq!(computer.run_back_to_write(15), Some(6));
+        assert_eq!(computer.pc(), 8);
+        assert_eq!(computer.get(15), 4);
+        assert_eq!(computer.pending_inputs(), &[0]);
+
+        assert_eq!(computer.run_back_to_write(14), None);
+        assert_eq!(computer.steps(), 6);
+        assert_eq!(computer.run_back_to_write(16), Some(4));
+        assert_eq!(computer.pending_inputs(), &[5, 0]);
+    }
+
+    #[test]
+    fn test_rewind_limits() {
+        let mut computer = IntcodeComputer::new(vec!(1101, 1, 1, 0, 1105, 1, 0));
+        computer.step().unwrap();
+        computer.set_history(true);
+        computer.step().unwrap();
+        computer.step().unwrap();
+
+        assert!(!computer.rewind_to(0));
+        assert!(!computer.rewind_to(4));
+        assert!(computer.rewind_to(1));
+        assert_eq!(computer.pc(), 4);
+        assert_eq!(computer.get(0), 2);
+
+        computer.set_history(false);
+        assert!(!computer.step_back());
+    }
+
+    #[test]
+    fn test_history_limit() {
+        let mut computer = IntcodeComputer::new(vec!(1001, 7, 1, 7, 1105, 1, 0, 0));
+        computer.set_history(true);
+        computer.set_history_limit(Some(3));
+        for _ in 0..6 {
+            computer.step().unwrap();
+        }
+
+        assert!(!computer.rewind_to(2));
+        assert!(computer.rewind_to(3));
+        assert_eq!(computer.get(7), 2);
+        assert!(!computer.step_back());
+    }
+
+    #[test]
+    fn test_profiling() {
+        let mut computer = IntcodeComputer::new(SUM.to_vec());
+        assert_eq!(computer.profile(), None);
+
+        computer.set_profiling(true);
+        computer.run(&mut IterInput::new(vec!(4, 5, 0)), &mut Vec::new()).unwrap();
+        let profile = computer.profile().unwrap();
+
+        // The HLT is counted but not executed
+        assert_eq!(profile.steps(), 12);
+        assert_eq!(computer.steps(), 11);
+        assert_eq!(profile.count(0), 3);
+        assert_eq!(profile.count(8), 2);
+        assert_eq!(profile.count(7), 1);
+        assert_eq!(profile.opcode_count("JT"), 5);
+        assert_eq!(profile.opcode_count("HLT"), 1);
+        assert_eq!(profile.hot_loops(), vec!(HotLoop { start: 0, end: 12, iterations: 2 }));
+        assert_eq!(profile.branch_counts(2), (2, 1));
+    }
+
+    #[test]
+    fn test_profiling_counts_halt_once() {
+        let mut computer = IntcodeComputer::new(vec!(99));
+        computer.set_profiling(true);
+        for _ in 0..3 {
+            assert_eq!(computer.resume(), Ok(Status::Halted));
+        }
+
+        assert_eq!(computer.profile().unwrap().opcode_count("HLT"), 1);
+        assert_eq!(computer.profile().unwrap().steps(), 1);
+    }
+
+    #[test]
+    fn test_self_modification_report() {
+        // Adds 1 to the ADD's own first parameter, then outputs it
+        let mut computer = IntcodeComputer::new(vec!(1001, 1, 1, 1, 4, 1, 99));
+        computer.set_self_modification(Some(SelfModification::Report));
+        let mut output = Vec::new();
+        computer.run(&mut IterInput::new(vec!()), &mut output).unwrap();
+
+        assert_eq!(output, vec!(2));
+        assert_eq!(computer.code_writes(), &[CodeWrite { step: 0, pc: 0, address: 1, old: 1, new: 2 }]);
+    }
+
+    #[test]
+    fn test_self_modification_fail() {
+        // Writing to data that is never executed is fine
+        let mut computer = IntcodeComputer::new(vec!(1101, 2, 3, 7, 4, 7, 99, 0));
+        computer.set_self_modification(Some(SelfModification::Fail));
+        let mut output = Vec::new();
+        assert_eq!(computer.run(&mut IterInput::new(vec!()), &mut output), Ok(()));
+        assert_eq!(output, vec!(5));
+
+        // Overwrites its own opcode with a HLT
+        let mut computer = IntcodeComputer::new(vec!(1101, 0, 99, 0, 1105, 1, 0));
+        computer.set_self_modification(Some(SelfModification::Fail));
+        let result = computer.run(&mut IterInput::new(vec!()), &mut Vec::new());
+
+        assert_eq!(result, Err(IntcodeError::new(0, ErrorKind::SelfModifyingCode(0))));
+        assert_eq!(computer.get(0), 1101);
+        assert_eq!(computer.code_writes(), &[]);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::disassembler::{disassemble, Line, Listing};
+use crate::instruction::Instruction;
+use crate::profile::Profile;
+use crate::Value;
+
+/// A conditional jump that was reached but only ever went one way.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct OneWayJump {
+    pub address: usize,
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+/// Which instructions of a program were executed, merged over any number of profiled runs.
+/// Only instructions found by `disassemble` are counted, so code that is written while the
+/// program runs isn't covered.
+#[derive(Debug)]
+pub struct Coverage {
+    listing: Listing,
+    runs: usize,
+    executed: HashMap<usize, u64>,
+    branches: HashMap<usize, (u64, u64)>,
+}
+
+impl Coverage {
+    pub fn new(program: &[Value]) -> Coverage {
+        Coverage {
+            listing: disassemble(program),
+            runs: 0,
+            executed: HashMap::new(),
+            branches: HashMap::new(),
+        }
+    }
+
+    /// Adds the counts from the profile of one run of the program.
+    pub fn add(&mut self, profile: &Profile) {
+        self.runs += 1;
+        for line in self.listing.lines() {
+            let address = line.address();
+            let count = profile.count(address);
+            if count > 0 {
+                *self.executed.entry(address).or_insert(0) += count;
+            }
+
+            let (taken, not_taken) = profile.branch_counts(address);
+            if taken + not_taken > 0 {
+                let counts = self.branches.entry(address).or_insert((0, 0));
+                counts.0 += taken;
+                counts.1 += not_taken;
+            }
+        }
+    }
+
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    fn instructions(&self) -> impl Iterator<Item = (usize, &Instruction)> {
+        self.listing.lines().iter().filter_map(|line| match line {
+            Line::Instruction { address, instruction, .. } => Some((*address, instruction)),
+            Line::Data { .. } => None,
+        })
+    }
+
+    /// The number of instructions in the program, and how many of them were executed.
+    pub fn covered(&self) -> (usize, usize) {
+        let total = self.instructions().count();
+        let covered = self.instructions().filter(|(address, _)| self.executed.contains_key(address)).count();
+        (total, covered)
+    }
+
+    /// The addresses of instructions no run executed.
+    pub fn never_executed(&self) -> Vec<usize> {
+        self.instructions()
+            .map(|(address, _)| address)
+            .filter(|address| !self.executed.contains_key(address))
+            .collect()
+    }
+
+    pub fn one_way_jumps(&self) -> Vec<OneWayJump> {
+        self.instructions()
+            .filter_map(|(address, _)| match self.branches.get(&address) {
+                Some(&(taken, not_taken)) if taken == 0 || not_taken == 0 => {
+                    Some(OneWayJump { address, taken, not_taken })
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The report as a single JSON object.
+    pub fn to_json(&self) -> String {
+        let (total, covered) = self.covered();
+        let never_executed: Vec<String> = self.never_executed().iter().map(|a| a.to_string()).collect();
+        let one_way_jumps: Vec<String> = self.one_way_jumps().iter()
+            .map(|jump| {
+                format!("{{\"address\":{},\"taken\":{},\"not_taken\":{}}}", jump.address, jump.taken, jump.not_taken)
+            })
+            .collect();
+
+        format!(
+            "{{\"runs\":{},\"instructions\":{},\"covered\":{},\"never_executed\":[{}],\"one_way_jumps\":[{}]}}",
+            self.runs,
+            total,
+            covered,
+            never_executed.join(","),
+            one_way_jumps.join(","),
+        )
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (total, covered) = self.covered();
+        let percent = 100.0 * covered as f64 / total.max(1) as f64;
+        let line = |address| &self.listing.lines()[self.listing.line_index(address).unwrap()];
+
+        writeln!(f, "Covered {} of {} instructions ({:.1}%) in {} runs", covered, total, percent, self.runs)?;
+
+        writeln!(f, "\nNever executed")?;
+        for address in self.never_executed() {
+            writeln!(f, "{}", self.listing.render(line(address)))?;
+        }
+
+        writeln!(f, "\nOne-way jumps")?;
+        for jump in self.one_way_jumps() {
+            let way = if jump.taken > 0 {
+                format!("always taken, {} times", jump.taken)
+            } else {
+                format!("never taken, {} times", jump.not_taken)
+            };
+            writeln!(f, "{:<60} {}", self.listing.render(line(jump.address)), way)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::computer::IntcodeComputer;
+    use crate::io::IterInput;
+
+    // Outputs 1 for a negative input, or 2 for any other, then halts
+    const SIGN: [Value; 16] = [3, 15, 1007, 15, 0, 15, 1005, 15, 12, 104, 2, 99, 104, 1, 99, 0];
+
+    fn coverage(runs: &[Value]) -> Coverage {
+        let mut coverage = Coverage::new(&SIGN);
+        for &input in runs {
+            let mut computer = IntcodeComputer::new(SIGN.to_vec());
+            computer.set_profiling(true);
+            computer.run(&mut IterInput::new(vec!(input)), &mut Vec::new()).unwrap();
+            coverage.add(computer.profile().unwrap());
+        }
+        coverage
+    }
+
+    #[test]
+    fn test_one_way() {
+        let coverage = coverage(&[5, 7]);
+
+        assert_eq!(coverage.runs(), 2);
+        assert_eq!(coverage.covered(), (7, 5));
+        assert_eq!(coverage.never_executed(), vec!(12, 14));
+        assert_eq!(coverage.one_way_jumps(), vec!(OneWayJump { address: 6, taken: 0, not_taken: 2 }));
+    }
+
+    #[test]
+    fn test_both_ways() {
+        let coverage = coverage(&[5, -3]);
+
+        assert_eq!(coverage.covered(), (7, 7));
+        assert_eq!(coverage.never_executed(), vec!());
+        assert_eq!(coverage.one_way_jumps(), vec!());
+    }
+
+    #[test]
+    fn test_jump_to_next_instruction() {
+        let mut computer = IntcodeComputer::new(vec!(1105, 1, 3, 99));
+        computer.set_profiling(true);
+        computer.run(&mut IterInput::new(vec!()), &mut Vec::new()).unwrap();
+        let mut coverage = Coverage::new(computer.program());
+        coverage.add(computer.profile().unwrap());
+
+        assert_eq!(coverage.covered(), (2, 2));
+        assert_eq!(coverage.one_way_jumps(), vec!(OneWayJump { address: 0, taken: 1, not_taken: 0 }));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(coverage(&[5, 7]).to_string(), "\
+Covered 5 of 7 instructions (71.4%) in 2 runs
+
+Never executed
+    12: 104 1                    OUT  #1
+    14: 99                       HLT
+
+One-way jumps
+     6: 1005 15 12               JT   [15], #L12             never taken, 2 times
+");
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            coverage(&[5, 7]).to_json(),
+            "{\"runs\":2,\"instructions\":7,\"covered\":5,\"never_executed\":[12,14],\
+             \"one_way_jumps\":[{\"address\":6,\"taken\":0,\"not_taken\":2}]}"
+        );
+    }
+}
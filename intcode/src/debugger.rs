@@ -0,0 +1,551 @@
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+use crate::computer::{IntcodeComputer, Status};
+use crate::disassembler::disassemble;
+use crate::watch::{WatchAction, Watchpoint};
+use crate::Value;
+
+/// The most memory cells `mem` shows at once.
+const MAX_MEM_COUNT: Value = 1000;
+
+/// How many of the most recent instructions can be undone.
+const HISTORY_LIMIT: usize = 100_000;
+
+const HELP: &str = "\
+step [n]             Execute n instructions (default 1)
+back [n]             Undo n instructions (default 1), going back at most 100000
+rewind <step>        Undo instructions until only <step> have been executed
+lastwrite <addr>     Undo instructions until just before the last write to an address
+continue             Run until a breakpoint, input is needed or the program halts
+break <addr|OP>      Break at an address or on every instruction with a mnemonic, e.g. OUT
+delete <addr|OP>     Remove a breakpoint
+breakpoints          List breakpoints
+watch <addr[-end]> [r|w|rw]
+                     Stop when memory is read or written (default rw)
+log <addr[-end]> [r|w|rw]
+                     Print memory reads or writes without stopping
+unwatch <addr>       Remove the watchpoints covering an address
+watchpoints          List watchpoints
+regs                 Show the pc, relative base, step count and pending input
+mem <addr> [count]   Show memory, up to 1000 cells at a time
+set <addr> <value>   Write memory, or use 'pc' or 'rb' as the address to set a register
+input <values...>    Queue values for the program to read
+list [n]             Disassemble n lines either side of the pc (default 4)
+quit                 Exit the debugger";
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Breakpoint {
+    Address(usize),
+    Mnemonic(String),
+}
+
+impl Breakpoint {
+    fn parse(text: &str) -> Breakpoint {
+        match text.parse() {
+            Ok(address) => Breakpoint::Address(address),
+            Err(_) => Breakpoint::Mnemonic(text.to_uppercase()),
+        }
+    }
+}
+
+pub struct Debugger {
+    computer: IntcodeComputer,
+    breakpoints: BTreeSet<Breakpoint>,
+}
+
+impl Debugger {
+    /// Turns on the computer's history so that execution can be wound back, keeping only
+    /// the most recent instructions so long runs don't use up memory.
+    pub fn new(mut computer: IntcodeComputer) -> Debugger {
+        computer.set_history(true);
+        computer.set_history_limit(Some(HISTORY_LIMIT));
+        Debugger {
+            computer,
+            breakpoints: BTreeSet::new(),
+        }
+    }
+
+    pub fn computer(&self) -> &IntcodeComputer {
+        &self.computer
+    }
+
+    /// Reads commands until `quit` or the end of `commands`. When `echo` is set each command
+    /// is written back after the prompt, so scripted sessions read like interactive ones.
+    pub fn run<R: BufRead, W: Write>(&mut self, commands: R, out: &mut W, echo: bool) -> io::Result<()> {
+        let mut lines = commands.lines();
+        loop {
+            write!(out, "(debug) ")?;
+            out.flush()?;
+
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => break writeln!(out),
+            };
+            if echo {
+                writeln!(out, "{}", line)?;
+            }
+            if !self.execute(line.trim(), out)? {
+                break Ok(());
+            }
+        }
+    }
+
+    fn is_breakpoint(&self) -> bool {
+        if self.breakpoints.contains(&Breakpoint::Address(self.computer.pc())) {
+            return true;
+        }
+        match self.computer.next_instruction() {
+            Ok(instruction) => self.breakpoints.contains(&Breakpoint::Mnemonic(instruction.mnemonic().to_string())),
+            Err(_) => false,
+        }
+    }
+
+    /// Executes one instruction, reporting anything the user should know about.
+    /// Returns false when execution can't carry on.
+    fn step<W: Write>(&mut self, out: &mut W) -> io::Result<bool> {
+        let status = self.computer.step();
+        for event in self.computer.take_watch_log() {
+            writeln!(out, "Log: {}", event)?;
+        }
+
+        match status {
+            Ok(Status::Running) => Ok(true),
+            Ok(Status::Output(value)) => {
+                writeln!(out, "Output: {}", value)?;
+                Ok(true)
+            },
+            Ok(Status::NeedsInput) => {
+                writeln!(out, "Waiting for input at {}", self.computer.pc())?;
+                Ok(false)
+            },
+            Ok(Status::Halted) => {
+                writeln!(out, "Halted at {}", self.computer.pc())?;
+                Ok(false)
+            },
+            Ok(Status::Watchpoint(event)) => {
+                writeln!(out, "Watchpoint: {}", event)?;
+                Ok(false)
+            },
+            Err(e) => {
+                writeln!(out, "Error: {}", e)?;
+                Ok(false)
+            },
+        }
+    }
+
+    fn list<W: Write>(&self, out: &mut W, context: usize) -> io::Result<()> {
+        let listing = disassemble(self.computer.program());
+        let current = match listing.line_index(self.computer.pc()) {
+            Some(index) => index,
+            None => return writeln!(out, "pc {} is outside the program", self.computer.pc()),
+        };
+
+        let start = current.saturating_sub(context);
+        let end = (current + context + 1).min(listing.lines().len());
+        for (index, line) in listing.lines()[start..end].iter().enumerate() {
+            let marker = if start + index == current { "=>" } else { "  " };
+            let breakpoint = if self.breakpoints.contains(&Breakpoint::Address(line.address())) { "*" } else { " " };
+            writeln!(out, "{}{}{}", marker, breakpoint, listing.render(line))?;
+        }
+        Ok(())
+    }
+
+    /// Runs a single command. Returns false when the user asked to quit.
+    pub fn execute<W: Write>(&mut self, command: &str, out: &mut W) -> io::Result<bool> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let number = |index: usize| -> Option<Value> { words.get(index).and_then(|word| word.parse().ok()) };
+
+        match words.as_slice() {
+            [] => (),
+            ["step"] | ["s"] | ["step", _] | ["s", _] => {
+                let count = match words.get(1) {
+                    Some(count) => match count.parse() {
+                        Ok(count) => count,
+                        Err(_) => return writeln!(out, "Invalid step count '{}'", count).map(|_| true),
+                    },
+                    None => 1,
+                };
+                for _ in 0..count {
+                    if !self.step(out)? {
+                        break;
+                    }
+                }
+                self.list(out, 0)?;
+            },
+            ["back"] | ["back", _] => {
+                let count = match words.get(1) {
+                    Some(count) => match count.parse() {
+                        Ok(count) => count,
+                        Err(_) => return writeln!(out, "Invalid step count '{}'", count).map(|_| true),
+                    },
+                    None => 1,
+                };
+                for _ in 0..count {
+                    if !self.computer.step_back() {
+                        writeln!(out, "No more history")?;
+                        break;
+                    }
+                }
+                self.list(out, 0)?;
+            },
+            ["rewind", _] => match number(1) {
+                Some(steps) if steps >= 0 && self.computer.rewind_to(steps as u64) => self.list(out, 0)?,
+                Some(steps) if steps >= 0 => writeln!(out, "Step {} is not in the history", steps)?,
+                _ => writeln!(out, "Usage: rewind <step>")?,
+            },
+            ["lastwrite", _] => match number(1) {
+                Some(address) if address >= 0 => match self.computer.run_back_to_write(address as usize) {
+                    Some(steps) => {
+                        writeln!(out, "Written by step {}", steps)?;
+                        self.list(out, 0)?;
+                    },
+                    None => writeln!(out, "No write to {} in the history", address)?,
+                },
+                _ => writeln!(out, "Usage: lastwrite <addr>")?,
+            },
+            ["continue"] | ["c"] => {
+                while self.step(out)? {
+                    if self.is_breakpoint() {
+                        writeln!(out, "Breakpoint at {}", self.computer.pc())?;
+                        break;
+                    }
+                }
+                self.list(out, 0)?;
+            },
+            ["break", target] | ["b", target] => {
+                self.breakpoints.insert(Breakpoint::parse(target));
+            },
+            ["delete", target] | ["d", target] => {
+                if !self.breakpoints.remove(&Breakpoint::parse(target)) {
+                    writeln!(out, "No breakpoint at {}", target)?;
+                }
+            },
+            ["breakpoints"] => {
+                for breakpoint in &self.breakpoints {
+                    match breakpoint {
+                        Breakpoint::Address(address) => writeln!(out, "{}", address)?,
+                        Breakpoint::Mnemonic(mnemonic) => writeln!(out, "{}", mnemonic)?,
+                    }
+                }
+            },
+            [command] | [command, _] | [command, _, _] if *command == "watch" || *command == "log" => {
+                match watchpoint(&words) {
+                    Some(watchpoint) => self.computer.add_watchpoint(watchpoint),
+                    None => writeln!(out, "Usage: {} <addr[-end]> [r|w|rw]", command)?,
+                }
+            },
+            ["unwatch", _] => match number(1) {
+                Some(address) if address >= 0 => self.computer.remove_watchpoints(address as usize),
+                _ => writeln!(out, "Usage: unwatch <addr>")?,
+            },
+            ["watchpoints"] => {
+                for watchpoint in self.computer.watchpoints() {
+                    let access = match (watchpoint.read, watchpoint.write) {
+                        (true, true) => "rw",
+                        (true, false) => "r",
+                        _ => "w",
+                    };
+                    let action = match watchpoint.action {
+                        WatchAction::Halt => "watch",
+                        WatchAction::Log => "log",
+                    };
+                    if watchpoint.start == watchpoint.end {
+                        writeln!(out, "{} {} {}", action, watchpoint.start, access)?;
+                    } else {
+                        writeln!(out, "{} {}-{} {}", action, watchpoint.start, watchpoint.end, access)?;
+                    }
+                }
+            },
+            ["regs"] | ["r"] => {
+                let inputs: Vec<String> = self.computer.pending_inputs().iter().map(|v| v.to_string()).collect();
+                writeln!(out, "pc: {}", self.computer.pc())?;
+                writeln!(out, "rb: {}", self.computer.relative_base())?;
+                writeln!(out, "steps: {}", self.computer.steps())?;
+                writeln!(out, "inputs: [{}]", inputs.join(", "))?;
+            },
+            [command] | [command, _] | [command, _, _] if *command == "mem" || *command == "x" => {
+                let limit = self.computer.memory_limit() as Value;
+                match (number(1), words.get(2).map(|_| number(2))) {
+                    (Some(address), _) if address >= limit => writeln!(out, "Address {} is out of range", address)?,
+                    (Some(address), None) if address >= 0 => {
+                        writeln!(out, "{}: {}", address, self.computer.get(address as usize))?;
+                    },
+                    (Some(address), Some(Some(count))) if address >= 0 && count >= 0 => {
+                        let end = address.saturating_add(count.min(MAX_MEM_COUNT)).min(limit);
+                        let values: Vec<String> = (address..end)
+                            .map(|a| self.computer.get(a as usize).to_string())
+                            .collect();
+                        writeln!(out, "{}: {}", address, values.join(" "))?;
+                    },
+                    _ => writeln!(out, "Usage: mem <addr> [count]")?,
+                }
+            },
+            ["set", "pc", _] => match number(2) {
+                Some(pc) if pc >= 0 => self.computer.set_pc(pc as usize),
+                _ => writeln!(out, "Invalid pc '{}'", words[2])?,
+            },
+            ["set", "rb", _] => match number(2) {
+                Some(relative_base) => self.computer.set_relative_base(relative_base),
+                None => writeln!(out, "Invalid relative base '{}'", words[2])?,
+            },
+            ["set", _, _] => match (number(1), number(2)) {
+                (Some(address), Some(value)) if address >= 0 => {
+                    if let Err(e) = self.computer.set(address as usize, value) {
+                        writeln!(out, "{}", e.kind())?;
+                    }
+                },
+                _ => writeln!(out, "Usage: set <addr> <value>")?,
+            },
+            _ if words.len() > 1 && words[0] == "input" => {
+                match words[1..].iter().map(|word| word.parse()).collect::<Result<Vec<Value>, _>>() {
+                    Ok(values) => values.into_iter().for_each(|value| self.computer.push_input(value)),
+                    Err(_) => writeln!(out, "Inputs must be numbers")?,
+                }
+            },
+            ["list"] | ["l"] | ["list", _] | ["l", _] => match words.get(1).map(|n| n.parse()) {
+                None => self.list(out, 4)?,
+                Some(Ok(context)) => self.list(out, context)?,
+                Some(Err(_)) => writeln!(out, "Usage: list [n]")?,
+            },
+            ["help"] | ["h"] => writeln!(out, "{}", HELP)?,
+            ["quit"] | ["q"] => return Ok(false),
+            _ => writeln!(out, "Unknown command '{}', try 'help'", command)?,
+        }
+
+        Ok(true)
+    }
+}
+
+/// Parses `watch <addr[-end]> [r|w|rw]` or the same for `log`.
+fn watchpoint(words: &[&str]) -> Option<Watchpoint> {
+    let action = if words[0] == "log" { WatchAction::Log } else { WatchAction::Halt };
+    let range = words.get(1)?;
+    let (start, end) = match range.find('-') {
+        Some(index) => (range[..index].parse().ok()?, range[index + 1..].parse().ok()?),
+        None => (range.parse().ok()?, range.parse().ok()?),
+    };
+    let (read, write) = match words.get(2) {
+        None | Some(&"rw") => (true, true),
+        Some(&"r") => (true, false),
+        Some(&"w") => (false, true),
+        Some(_) => return None,
+    };
+
+    if start > end {
+        return None;
+    }
+    Some(Watchpoint { start, end, read, write, action })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::DOUBLER;
+
+    fn session(program: Vec<Value>, commands: &str) -> (Debugger, String) {
+        let mut debugger = Debugger::new(IntcodeComputer::new(program));
+        let mut out = Vec::new();
+        debugger.run(commands.as_bytes(), &mut out, true).unwrap();
+        (debugger, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_step() {
+        let (debugger, out) = session(DOUBLER.to_vec(), "input 3\nstep\ns 3\nquit\n");
+
+        assert_eq!(out, "\
+(debug) input 3
+(debug) step
+=>      2: 1006 15 14               JF   [15], #L14
+(debug) s 3
+Output: 6
+=>     11: 1105 1 0                 JT   #1, #L0
+(debug) quit
+");
+        assert_eq!(debugger.computer().pc(), 11);
+    }
+
+    #[test]
+    fn test_continue_to_breakpoints() {
+        let (_, out) = session(DOUBLER.to_vec(), "break 9\nb mul\nbreakpoints\ninput 3 4 0\nc\nc\nc\ndelete 9\nd MUL\nc\nc\nq\n");
+
+        assert_eq!(out, "\
+(debug) break 9
+(debug) b mul
+(debug) breakpoints
+9
+MUL
+(debug) input 3 4 0
+(debug) c
+Breakpoint at 5
+=>      5: 102 2 15 15              MUL  #2, [15], [15]
+(debug) c
+Breakpoint at 9
+=>*     9: 4 15                     OUT  [15]
+(debug) c
+Output: 6
+Breakpoint at 5
+=>      5: 102 2 15 15              MUL  #2, [15], [15]
+(debug) delete 9
+(debug) d MUL
+(debug) c
+Output: 8
+Halted at 14
+=>     14: 99                       HLT
+(debug) c
+Halted at 14
+=>     14: 99                       HLT
+(debug) q
+");
+    }
+
+    #[test]
+    fn test_continue_waits_for_input() {
+        let (_, out) = session(DOUBLER.to_vec(), "continue\nquit\n");
+
+        assert_eq!(out, "\
+(debug) continue
+Waiting for input at 0
+=>      0: 3 15                     IN   [15]
+(debug) quit
+");
+    }
+
+    #[test]
+    fn test_registers_and_memory() {
+        let (debugger, out) = session(DOUBLER.to_vec(), "set 15 7\nset pc 5\nset rb -2\ninput 1 2\nregs\nmem 15\nx 13 3\nlist 1\nquit\n");
+
+        assert_eq!(out, "\
+(debug) set 15 7
+(debug) set pc 5
+(debug) set rb -2
+(debug) input 1 2
+(debug) regs
+pc: 5
+rb: -2
+steps: 0
+inputs: [1, 2]
+(debug) mem 15
+15: 7
+(debug) x 13 3
+13: 0 99 7
+(debug) list 1
+        2: 1006 15 14               JF   [15], #L14
+=>      5: 102 2 15 15              MUL  #2, [15], [15]
+        9: 4 15                     OUT  [15]
+(debug) quit
+");
+        assert_eq!(debugger.computer().get(15), 7);
+    }
+
+    #[test]
+    fn test_errors() {
+        let (_, out) = session(vec!(1105, 1, -1), "jump\nstep x\nmem\nset a b\nstep\nquit\n");
+
+        assert_eq!(out, "\
+(debug) jump
+Unknown command 'jump', try 'help'
+(debug) step x
+Invalid step count 'x'
+(debug) mem
+Usage: mem <addr> [count]
+(debug) set a b
+Usage: set <addr> <value>
+(debug) step
+Error: Invalid address -1 at pc 0
+=>      0: 1105 1 -1                JT   #1, #-1
+(debug) quit
+");
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        let mut computer = IntcodeComputer::new(DOUBLER.to_vec());
+        computer.set_memory_limit(20);
+        let mut debugger = Debugger::new(computer);
+        let mut out = Vec::new();
+        let commands = "mem 14 9223372036854775807\nmem 20\nset 19 4\nset 99999999999999 1\nquit\n";
+        debugger.run(commands.as_bytes(), &mut out, true).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "\
+(debug) mem 14 9223372036854775807
+14: 99 0 0 0 0 0
+(debug) mem 20
+Address 20 is out of range
+(debug) set 19 4
+(debug) set 99999999999999 1
+Address 99999999999999 is out of range
+(debug) quit
+");
+        assert_eq!(debugger.computer().program().len(), 20);
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let commands = "watch 15 w\nlog 14-15 r\nwatchpoints\ninput 3 0\nc\nc\nc\nunwatch 15\nwatchpoints\nc\nwatch 3-1\nq\n";
+        let (_, out) = session(DOUBLER.to_vec(), commands);
+
+        assert_eq!(out, "\
+(debug) watch 15 w
+(debug) log 14-15 r
+(debug) watchpoints
+watch 15 w
+log 14-15 r
+(debug) input 3 0
+(debug) c
+Watchpoint: wrote 3 to 15 at pc 0, was 0
+=>      2: 1006 15 14               JF   [15], #L14
+(debug) c
+Log: read 3 from 15 at pc 2
+Log: read 3 from 15 at pc 5
+Watchpoint: wrote 6 to 15 at pc 5, was 3
+=>      9: 4 15                     OUT  [15]
+(debug) c
+Log: read 6 from 15 at pc 9
+Output: 6
+Watchpoint: wrote 0 to 15 at pc 0, was 6
+=>      2: 1006 15 14               JF   [15], #L14
+(debug) unwatch 15
+(debug) watchpoints
+(debug) c
+Halted at 14
+=>     14: 99                       HLT
+(debug) watch 3-1
+Usage: watch <addr[-end]> [r|w|rw]
+(debug) q
+");
+    }
+
+    #[test]
+    fn test_reverse() {
+        let commands = "input 3 4\ns 6\nback 2\nlastwrite 15\nregs\nrewind 9\nrewind 0\nback\nquit\n";
+        let (debugger, out) = session(DOUBLER.to_vec(), commands);
+
+        assert_eq!(out, "\
+(debug) input 3 4
+(debug) s 6
+Output: 6
+=>      2: 1006 15 14               JF   [15], #L14
+(debug) back 2
+=>     11: 1105 1 0                 JT   #1, #L0
+(debug) lastwrite 15
+Written by step 2
+=>      5: 102 2 15 15              MUL  #2, [15], [15]
+(debug) regs
+pc: 5
+rb: 0
+steps: 2
+inputs: [4]
+(debug) rewind 9
+Step 9 is not in the history
+(debug) rewind 0
+=>      0: 3 15                     IN   [15]
+(debug) back
+No more history
+=>      0: 3 15                     IN   [15]
+(debug) quit
+");
+        assert_eq!(debugger.computer().steps(), 0);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::instruction::{Instruction, Parameter};
+use crate::Value;
+
+#[derive(Debug, PartialEq)]
+pub enum Line {
+    Instruction { address: usize, words: Vec<Value>, instruction: Instruction },
+    /// A word that doesn't decode as an instruction.
+    Data { address: usize, value: Value },
+}
+
+impl Line {
+    pub fn address(&self) -> usize {
+        match self {
+            Line::Instruction { address, .. } | Line::Data { address, .. } => *address,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Line::Instruction { words, .. } => words.len(),
+            Line::Data { .. } => 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Listing {
+    lines: Vec<Line>,
+    labels: BTreeMap<usize, String>,
+}
+
+impl Listing {
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// The label given to an address that an immediate jump targets.
+    pub fn label(&self, address: usize) -> Option<&str> {
+        self.labels.get(&address).map(|label| label.as_str())
+    }
+
+    /// The index of the line that covers `address`.
+    pub fn line_index(&self, address: usize) -> Option<usize> {
+        self.lines.iter().position(|line| line.address() <= address && address < line.address() + line.size())
+    }
+
+    fn render_parameter(&self, parameter: &Parameter, is_target: bool) -> String {
+        match parameter {
+            Parameter::Immediate(target) if is_target && *target >= 0 => match self.label(*target as usize) {
+                Some(label) => format!("#{}", label),
+                None => parameter.to_string(),
+            },
+            _ => parameter.to_string(),
+        }
+    }
+
+    /// Renders a single line as its address, raw words, mnemonic and operands.
+    pub fn render(&self, line: &Line) -> String {
+        match line {
+            Line::Instruction { address, words, instruction } => {
+                let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+                let is_jump = jump_target(instruction).is_some();
+                let parameters: Vec<&Parameter> = instruction.parameters();
+                let operands: Vec<String> = parameters.iter().enumerate()
+                    .map(|(i, p)| self.render_parameter(p, is_jump && i == 1))
+                    .collect();
+
+                format!("{:>6}: {:<24} {:<4} {}", address, words.join(" "), instruction.mnemonic(), operands.join(", "))
+                    .trim_end()
+                    .to_string()
+            },
+            Line::Data { address, value } => format!("{:>6}: {:<24} DATA {}", address, value, value),
+        }
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            if let Some(label) = self.label(line.address()) {
+                writeln!(f, "{}:", label)?;
+            }
+            writeln!(f, "{}", self.render(line))?;
+        }
+        Ok(())
+    }
+}
+
+fn jump_target(instruction: &Instruction) -> Option<&Parameter> {
+    match instruction {
+        Instruction::JumpIfTrue(_, target) | Instruction::JumpIfFalse(_, target) => Some(target),
+        _ => None,
+    }
+}
+
+/// Decodes a program from start to end, treating any word that isn't a valid instruction as data.
+pub fn disassemble(program: &[Value]) -> Listing {
+    let mut lines = Vec::new();
+    let mut address = 0;
+
+    while address < program.len() {
+        match Instruction::parse(&program[address..]) {
+            Ok(instruction) => {
+                let size = instruction.size();
+                lines.push(Line::Instruction {
+                    address,
+                    words: program[address..address + size].to_vec(),
+                    instruction,
+                });
+                address += size;
+            },
+            Err(_) => {
+                lines.push(Line::Data { address, value: program[address] });
+                address += 1;
+            },
+        }
+    }
+
+    let starts: Vec<usize> = lines.iter().map(|line| line.address()).collect();
+    let labels = lines.iter()
+        .filter_map(|line| match line {
+            Line::Instruction { instruction, .. } => jump_target(instruction),
+            Line::Data { .. } => None,
+        })
+        .filter_map(|target| match target {
+            Parameter::Immediate(target) if *target >= 0 => Some(*target as usize),
+            _ => None,
+        })
+        .filter(|target| starts.binary_search(target).is_ok())
+        .map(|target| (target, format!("L{}", target)))
+        .collect();
+
+    Listing { lines, labels }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::DOUBLER;
+
+    #[test]
+    fn test_disassemble() {
+        let listing = disassemble(&DOUBLER);
+
+        assert_eq!(listing.to_string(), "\
+L0:
+     0: 3 15                     IN   [15]
+     2: 1006 15 14               JF   [15], #L14
+     5: 102 2 15 15              MUL  #2, [15], [15]
+     9: 4 15                     OUT  [15]
+    11: 1105 1 0                 JT   #1, #L0
+L14:
+    14: 99                       HLT
+    15: 0                        DATA 0
+");
+    }
+
+    #[test]
+    fn test_disassemble_data() {
+        let listing = disassemble(&[104, -3, 55, 11101, 1]);
+
+        assert_eq!(listing.lines(), &[
+            Line::Instruction { address: 0, words: vec!(104, -3), instruction: Instruction::Output(Parameter::Immediate(-3)) },
+            Line::Data { address: 2, value: 55 },
+            Line::Data { address: 3, value: 11101 },
+            Line::Data { address: 4, value: 1 },
+        ]);
+    }
+
+    #[test]
+    fn test_labels_only_at_line_starts() {
+        // The jump lands inside the OUT instruction, so it can't be labelled
+        let listing = disassemble(&[1105, 1, 4, 104, 5, 99]);
+
+        assert_eq!(listing.label(4), None);
+        assert_eq!(listing.render(&listing.lines()[0]), "     0: 1105 1 4                 JT   #1, #4");
+    }
+
+    #[test]
+    fn test_line_index() {
+        let listing = disassemble(&[1101, 1, 2, 3, 99]);
+
+        assert_eq!(listing.line_index(0), Some(0));
+        assert_eq!(listing.line_index(3), Some(0));
+        assert_eq!(listing.line_index(4), Some(1));
+        assert_eq!(listing.line_index(5), None);
+    }
+}
@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::Value;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ErrorKind {
+    /// The raw instruction word did not contain a known opcode.
+    UnknownOpcode(Value),
+    /// `parameter` counts from 1 for the first parameter after the opcode.
+    InvalidParameterMode { opcode: Value, parameter: usize, mode: Value },
+    /// The instruction ended before all of its parameters.
+    TruncatedInstruction,
+    /// A memory access or jump used a negative address.
+    InvalidAddress(Value),
+    /// A memory access or jump went past the memory limit.
+    AddressOutOfRange(Value),
+    /// An arithmetic instruction's result didn't fit in a `Value`.
+    Overflow,
+    NoInput,
+    /// The program halted before giving the output that was needed.
+    NoOutput,
+    StepLimitExceeded(u64),
+    /// The machine returned to an earlier state without reading input, so it will never halt.
+    InfiniteLoop,
+    /// The program tried to write to this address after executing it as part of an instruction.
+    SelfModifyingCode(usize),
+    /// Writing the execution trace failed.
+    TraceFailed(String),
+    /// A networked machine sent a packet to an address with no machine.
+    UnknownDestination(Value),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnknownOpcode(opcode) => write!(f, "Unexpected opcode {}", opcode),
+            ErrorKind::InvalidParameterMode { opcode, parameter, mode } => {
+                write!(f, "Unexpected mode {} for parameter {} of {}", mode, parameter, opcode)
+            },
+            ErrorKind::TruncatedInstruction => write!(f, "Truncated instruction"),
+            ErrorKind::InvalidAddress(address) => write!(f, "Invalid address {}", address),
+            ErrorKind::AddressOutOfRange(address) => write!(f, "Address {} is out of range", address),
+            ErrorKind::Overflow => write!(f, "Arithmetic overflow"),
+            ErrorKind::NoInput => write!(f, "No input available"),
+            ErrorKind::NoOutput => write!(f, "Halted without output"),
+            ErrorKind::StepLimitExceeded(limit) => write!(f, "Exceeded the limit of {} steps", limit),
+            ErrorKind::InfiniteLoop => write!(f, "Infinite loop detected"),
+            ErrorKind::SelfModifyingCode(address) => write!(f, "Write to executed code at {}", address),
+            ErrorKind::TraceFailed(message) => write!(f, "Unable to write trace: {}", message),
+            ErrorKind::UnknownDestination(address) => write!(f, "Packet sent to unknown address {}", address),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IntcodeError {
+    pc: usize,
+    kind: ErrorKind,
+}
+
+impl IntcodeError {
+    pub fn new(pc: usize, kind: ErrorKind) -> IntcodeError {
+        IntcodeError { pc, kind }
+    }
+
+    /// The address of the instruction that failed.
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at pc {}", self.kind, self.pc)
+    }
+}
+
+impl Error for IntcodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = IntcodeError::new(12, ErrorKind::InvalidParameterMode {
+            opcode: 1301,
+            parameter: 1,
+            mode: 3,
+        });
+
+        assert_eq!(error.to_string(), "Unexpected mode 3 for parameter 1 of 1301 at pc 12");
+    }
+}
@@ -0,0 +1,44 @@
+//! Programs shared by the tests.
+
+use crate::computer::IntcodeComputer;
+use crate::runtime::Machines;
+use crate::Value;
+
+/// Outputs each input doubled until it reads a zero.
+pub const DOUBLER: [Value; 16] = [3, 15, 1006, 15, 14, 102, 2, 15, 15, 4, 15, 1105, 1, 0, 99, 0];
+
+/// Adds up the inputs in [15] until it reads a zero, then outputs the total.
+pub const SUM: [Value; 17] = [3, 16, 1005, 16, 8, 4, 15, 99, 1, 15, 16, 15, 1105, 1, 0, 0, 0];
+
+/// The first amplifier chain example from day 7, giving 43210 with phases 4, 3, 2, 1, 0.
+pub const AMPLIFIER_CHAIN: [Value; 17] = [3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0];
+
+/// The first feedback loop example from day 7, giving 139629729 with phases 9, 8, 7, 6, 5.
+pub const AMPLIFIER_FEEDBACK: [Value; 29] = [
+    3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28, 1005, 28, 6, 99, 0, 0, 5,
+];
+
+/// Reads two values after its phase setting but only outputs one, so a loop of them deadlocks.
+pub const READS_TWO_SENDS_ONE: [Value; 9] = [3, 0, 3, 0, 3, 0, 4, 0, 99];
+
+/// One machine per phase setting running `program`, each sending its output to the next.
+/// The first machine also gets a 0 to start with, and with `feedback` the last machine sends
+/// its output back to the first.
+pub fn amplifiers(program: &[Value], phases: &[Value], feedback: bool) -> Machines {
+    let mut machines = Machines::new();
+    for (id, &phase) in phases.iter().enumerate() {
+        let mut computer = IntcodeComputer::new(program.to_vec());
+        computer.push_input(phase);
+        if id == 0 {
+            computer.push_input(0);
+        }
+        machines.add(computer);
+    }
+    for id in 1..phases.len() {
+        machines.connect(id - 1, id);
+    }
+    if feedback {
+        machines.connect(phases.len() - 1, 0);
+    }
+    machines
+}
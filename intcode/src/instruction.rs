@@ -0,0 +1,324 @@
+use std::fmt;
+
+use crate::error::ErrorKind;
+use crate::Value;
+
+pub(crate) const ADD: Value = 1;
+pub(crate) const MULTIPLY: Value = 2;
+pub(crate) const INPUT: Value = 3;
+pub(crate) const OUTPUT: Value = 4;
+pub(crate) const JUMPIFTRUE: Value = 5;
+pub(crate) const JUMPIFFALSE: Value = 6;
+pub(crate) const LESSTHAN: Value = 7;
+pub(crate) const EQUALS: Value = 8;
+pub(crate) const ADJUSTRELATIVEBASE: Value = 9;
+pub(crate) const HALT: Value = 99;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Parameter {
+    Position(Value),
+    Immediate(Value),
+    Relative(Value),
+}
+
+impl Parameter {
+    pub fn new(value: Value, mode: Value) -> Option<Parameter> {
+        match mode {
+            0 => Some(Parameter::Position(value)),
+            1 => Some(Parameter::Immediate(value)),
+            2 => Some(Parameter::Relative(value)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Parameter::Position(address) => write!(f, "[{}]", address),
+            Parameter::Immediate(value) => write!(f, "#{}", value),
+            Parameter::Relative(offset) if *offset < 0 => write!(f, "[rb{}]", offset),
+            Parameter::Relative(offset) => write!(f, "[rb+{}]", offset),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Instruction {
+    Add(Parameter, Parameter, Parameter),
+    Multiply(Parameter, Parameter, Parameter),
+    Input(Parameter),
+    Output(Parameter),
+    JumpIfTrue(Parameter, Parameter),
+    JumpIfFalse(Parameter, Parameter),
+    LessThan(Parameter, Parameter, Parameter),
+    Equals(Parameter, Parameter, Parameter),
+    AdjustRelativeBase(Parameter),
+    Halt,
+}
+
+impl Instruction {
+    pub fn parse(program: &[Value]) -> Result<Instruction, ErrorKind> {
+        let word = *program.first().ok_or(ErrorKind::TruncatedInstruction)?;
+        let opcode = word % 100;
+        let parameter = |index: usize| {
+            let value = *program.get(index).ok_or(ErrorKind::TruncatedInstruction)?;
+            let mode = (word / Value::pow(10, index as u32 + 1)) % 10;
+            Parameter::new(value, mode).ok_or(ErrorKind::InvalidParameterMode {
+                opcode: word,
+                parameter: index,
+                mode,
+            })
+        };
+        let destination = |index: usize| match parameter(index)? {
+            Parameter::Immediate(_) => Err(ErrorKind::InvalidParameterMode {
+                opcode: word,
+                parameter: index,
+                mode: 1,
+            }),
+            target => Ok(target),
+        };
+
+        match opcode {
+            ADD => Ok(Instruction::Add(
+                parameter(1)?,
+                parameter(2)?,
+                destination(3)?,
+            )),
+            MULTIPLY => Ok(Instruction::Multiply(
+                parameter(1)?,
+                parameter(2)?,
+                destination(3)?,
+            )),
+            INPUT => Ok(Instruction::Input(
+                destination(1)?
+            )),
+            OUTPUT => Ok(Instruction::Output(
+                parameter(1)?
+            )),
+            JUMPIFTRUE => Ok(Instruction::JumpIfTrue(
+                parameter(1)?,
+                parameter(2)?
+            )),
+            JUMPIFFALSE => Ok(Instruction::JumpIfFalse(
+                parameter(1)?,
+                parameter(2)?
+            )),
+            LESSTHAN => Ok(Instruction::LessThan(
+                parameter(1)?,
+                parameter(2)?,
+                destination(3)?
+            )),
+            EQUALS => Ok(Instruction::Equals(
+                parameter(1)?,
+                parameter(2)?,
+                destination(3)?
+            )),
+            ADJUSTRELATIVEBASE => Ok(Instruction::AdjustRelativeBase(
+                parameter(1)?
+            )),
+            HALT => Ok(Instruction::Halt),
+            _ => Err(ErrorKind::UnknownOpcode(word)),
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Add(..) => "ADD",
+            Instruction::Multiply(..) => "MUL",
+            Instruction::Input(..) => "IN",
+            Instruction::Output(..) => "OUT",
+            Instruction::JumpIfTrue(..) => "JT",
+            Instruction::JumpIfFalse(..) => "JF",
+            Instruction::LessThan(..) => "LT",
+            Instruction::Equals(..) => "EQ",
+            Instruction::AdjustRelativeBase(..) => "ARB",
+            Instruction::Halt => "HLT",
+        }
+    }
+
+    pub fn parameters(&self) -> Vec<&Parameter> {
+        match self {
+            Instruction::Add(a, b, c)
+            | Instruction::Multiply(a, b, c)
+            | Instruction::LessThan(a, b, c)
+            | Instruction::Equals(a, b, c) => vec!(a, b, c),
+            Instruction::JumpIfTrue(a, b) | Instruction::JumpIfFalse(a, b) => vec!(a, b),
+            Instruction::Input(a) | Instruction::Output(a) | Instruction::AdjustRelativeBase(a) => vec!(a),
+            Instruction::Halt => vec!(),
+        }
+    }
+
+    /// The number of words the instruction occupies, including the opcode.
+    pub fn size(&self) -> usize {
+        self.parameters().len() + 1
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parameters: Vec<String> = self.parameters().iter().map(|p| p.to_string()).collect();
+        if parameters.is_empty() {
+            write!(f, "{}", self.mnemonic())
+        } else {
+            write!(f, "{:<4} {}", self.mnemonic(), parameters.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_parse_add() {
+        let instruction = Instruction::parse(&[101, 3, 4, 5, 6, 7]).unwrap();
+
+        assert_eq!(instruction, Instruction::Add(
+            Parameter::Immediate(3),
+            Parameter::Position(4),
+            Parameter::Position(5)
+        ));
+    }
+
+    #[test]
+    fn test_instruction_parse_multiply() {
+        let instruction = Instruction::parse(&[1002, 3, 4, 5, 6, 7]).unwrap();
+
+        assert_eq!(instruction, Instruction::Multiply(
+            Parameter::Position(3),
+            Parameter::Immediate(4),
+            Parameter::Position(5)
+        ));
+    }
+
+    #[test]
+    fn test_instruction_parse_input() {
+        let instruction = Instruction::parse(&[203, 18]).unwrap();
+
+        assert_eq!(instruction, Instruction::Input(
+            Parameter::Relative(18)
+        ));
+    }
+
+    #[test]
+    fn test_instruction_parse_output() {
+        let instruction = Instruction::parse(&[4, 56]).unwrap();
+
+        assert_eq!(instruction, Instruction::Output(
+            Parameter::Position(56)
+        ));
+    }
+
+    #[test]
+    fn test_instruction_parse_relative() {
+        let instruction = Instruction::parse(&[22207, -1, 3, 5]).unwrap();
+
+        assert_eq!(instruction, Instruction::LessThan(
+            Parameter::Relative(-1),
+            Parameter::Relative(3),
+            Parameter::Relative(5)
+        ));
+    }
+
+    #[test]
+    fn test_instruction_parse_adjust_relative_base() {
+        let instruction = Instruction::parse(&[209, -4]).unwrap();
+
+        assert_eq!(instruction, Instruction::AdjustRelativeBase(
+            Parameter::Relative(-4)
+        ));
+    }
+
+    #[test]
+    fn test_instruction_parse_halt() {
+        let instruction = Instruction::parse(&[99, 100]).unwrap();
+
+        assert_eq!(instruction, Instruction::Halt);
+    }
+
+    #[test]
+    fn test_instruction_parse_bad_opcode() {
+        let instruction = Instruction::parse(&[23, 100]);
+
+        assert_eq!(instruction, Err(ErrorKind::UnknownOpcode(23)));
+    }
+
+    #[test]
+    fn test_instruction_parse_truncated() {
+        assert_eq!(Instruction::parse(&[]), Err(ErrorKind::TruncatedInstruction));
+        assert_eq!(Instruction::parse(&[1, 2, 3]), Err(ErrorKind::TruncatedInstruction));
+        assert_eq!(Instruction::parse(&[4]), Err(ErrorKind::TruncatedInstruction));
+    }
+
+    #[test]
+    fn test_instruction_parse_bad_parameter_type() {
+        let instruction = Instruction::parse(&[1301, 100, 101, 102]);
+
+        assert_eq!(instruction, Err(ErrorKind::InvalidParameterMode {
+            opcode: 1301,
+            parameter: 1,
+            mode: 3,
+        }));
+    }
+
+    #[test]
+    fn test_instruction_size() {
+        assert_eq!(Instruction::parse(&[1, 0, 0, 0]).unwrap().size(), 4);
+        assert_eq!(Instruction::parse(&[5, 0, 0]).unwrap().size(), 3);
+        assert_eq!(Instruction::parse(&[4, 0]).unwrap().size(), 2);
+        assert_eq!(Instruction::parse(&[99]).unwrap().size(), 1);
+    }
+
+    #[test]
+    fn test_instruction_display() {
+        assert_eq!(Instruction::parse(&[21101, 5, -2, 3]).unwrap().to_string(), "ADD  #5, #-2, [rb+3]");
+        assert_eq!(Instruction::parse(&[204, -1]).unwrap().to_string(), "OUT  [rb-1]");
+        assert_eq!(Instruction::parse(&[1006, 12, 0]).unwrap().to_string(), "JF   [12], #0");
+        assert_eq!(Instruction::parse(&[99]).unwrap().to_string(), "HLT");
+    }
+
+    macro_rules! test_parse {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (program, expected): (&[Value], Result<Instruction, ErrorKind>) = $value;
+
+                    assert_eq!(Instruction::parse(program), expected);
+                }
+            )*
+        }
+    }
+
+    fn invalid_write(opcode: Value, parameter: usize) -> ErrorKind {
+        ErrorKind::InvalidParameterMode { opcode, parameter, mode: 1 }
+    }
+
+    // Every opcode with each mode a parameter may take, and immediate destinations rejected
+    test_parse! {
+        test_parse_add: (&[1, 1, 2, 3], Ok(Instruction::Add(Parameter::Position(1), Parameter::Position(2), Parameter::Position(3)))),
+        test_parse_add_relative_destination: (&[21101, 1, 2, -3], Ok(Instruction::Add(Parameter::Immediate(1), Parameter::Immediate(2), Parameter::Relative(-3)))),
+        test_parse_add_immediate_destination: (&[11101, 1, 2, 3], Err(invalid_write(11101, 3))),
+        test_parse_multiply: (&[1202, 1, 2, 3], Ok(Instruction::Multiply(Parameter::Relative(1), Parameter::Immediate(2), Parameter::Position(3)))),
+        test_parse_multiply_relative_destination: (&[20002, 1, 2, 3], Ok(Instruction::Multiply(Parameter::Position(1), Parameter::Position(2), Parameter::Relative(3)))),
+        test_parse_multiply_immediate_destination: (&[10002, 1, 2, 3], Err(invalid_write(10002, 3))),
+        test_parse_input: (&[3, 7], Ok(Instruction::Input(Parameter::Position(7)))),
+        test_parse_input_relative_destination: (&[203, -7], Ok(Instruction::Input(Parameter::Relative(-7)))),
+        test_parse_input_immediate_destination: (&[103, 7], Err(invalid_write(103, 1))),
+        test_parse_output_position: (&[4, 7], Ok(Instruction::Output(Parameter::Position(7)))),
+        test_parse_output_immediate: (&[104, 7], Ok(Instruction::Output(Parameter::Immediate(7)))),
+        test_parse_output_relative: (&[204, 7], Ok(Instruction::Output(Parameter::Relative(7)))),
+        test_parse_jump_if_true: (&[1205, 1, 2], Ok(Instruction::JumpIfTrue(Parameter::Relative(1), Parameter::Immediate(2)))),
+        test_parse_jump_if_false: (&[2106, 1, 2], Ok(Instruction::JumpIfFalse(Parameter::Immediate(1), Parameter::Relative(2)))),
+        test_parse_less_than: (&[1107, 1, 2, 3], Ok(Instruction::LessThan(Parameter::Immediate(1), Parameter::Immediate(2), Parameter::Position(3)))),
+        test_parse_less_than_relative_destination: (&[20007, 1, 2, 3], Ok(Instruction::LessThan(Parameter::Position(1), Parameter::Position(2), Parameter::Relative(3)))),
+        test_parse_less_than_immediate_destination: (&[10007, 1, 2, 3], Err(invalid_write(10007, 3))),
+        test_parse_equals: (&[8, 1, 2, 3], Ok(Instruction::Equals(Parameter::Position(1), Parameter::Position(2), Parameter::Position(3)))),
+        test_parse_equals_relative_destination: (&[22208, 1, 2, 3], Ok(Instruction::Equals(Parameter::Relative(1), Parameter::Relative(2), Parameter::Relative(3)))),
+        test_parse_equals_immediate_destination: (&[11108, 1, 2, 3], Err(invalid_write(11108, 3))),
+        test_parse_adjust_relative_base_position: (&[9, 4], Ok(Instruction::AdjustRelativeBase(Parameter::Position(4)))),
+        test_parse_adjust_relative_base_immediate: (&[109, -4], Ok(Instruction::AdjustRelativeBase(Parameter::Immediate(-4)))),
+        test_parse_halt: (&[99], Ok(Instruction::Halt)),
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::io;
+
+use crate::Value;
+
+pub trait Input {
+    /// Returns the next value for the program, or `None` once the input is exhausted.
+    fn read(&mut self) -> Option<Value>;
+}
+
+pub trait Output {
+    fn write(&mut self, value: Value);
+}
+
+impl Input for io::Stdin {
+    fn read(&mut self) -> Option<Value> {
+        loop {
+            println!("Enter a number:");
+            let mut input = String::new();
+            match self.read_line(&mut input) {
+                Ok(0) => break None,
+                Ok(_) => {
+                    match input.trim().parse() {
+                        Ok(value) => break Some(value),
+                        Err(_) => println!("Not a valid number"),
+                    }
+                },
+                Err(_) => println!("Failed to read input"),
+            }
+        }
+    }
+}
+
+impl Output for io::Stdout {
+    fn write(&mut self, value: Value) {
+        println!("{}", value);
+    }
+}
+
+impl Input for VecDeque<Value> {
+    fn read(&mut self) -> Option<Value> {
+        self.pop_front()
+    }
+}
+
+impl Output for VecDeque<Value> {
+    fn write(&mut self, value: Value) {
+        self.push_back(value);
+    }
+}
+
+impl Output for Vec<Value> {
+    fn write(&mut self, value: Value) {
+        self.push(value);
+    }
+}
+
+impl<F: FnMut() -> Option<Value>> Input for F {
+    fn read(&mut self) -> Option<Value> {
+        self()
+    }
+}
+
+impl<F: FnMut(Value)> Output for F {
+    fn write(&mut self, value: Value) {
+        self(value)
+    }
+}
+
+/// Feeds a program from any iterator of values.
+pub struct IterInput<I>(I);
+
+impl<I: Iterator<Item = Value>> IterInput<I> {
+    pub fn new<T: IntoIterator<IntoIter = I, Item = Value>>(values: T) -> IterInput<I> {
+        IterInput(values.into_iter())
+    }
+}
+
+impl<I: Iterator<Item = Value>> Input for IterInput<I> {
+    fn read(&mut self) -> Option<Value> {
+        self.0.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vec_deque_input() {
+        let mut input: VecDeque<Value> = vec!(1, 2).into_iter().collect();
+
+        assert_eq!(input.read(), Some(1));
+        assert_eq!(input.read(), Some(2));
+        assert_eq!(input.read(), None);
+    }
+
+    #[test]
+    fn test_iter_input() {
+        let mut input = IterInput::new(vec!(5, 6));
+
+        assert_eq!(input.read(), Some(5));
+        assert_eq!(input.read(), Some(6));
+        assert_eq!(input.read(), None);
+    }
+
+    #[test]
+    fn test_closure_input_and_output() {
+        let mut next = 0;
+        let mut input = || {
+            next += 1;
+            Some(next)
+        };
+        assert_eq!(input.read(), Some(1));
+        assert_eq!(input.read(), Some(2));
+
+        let mut total = 0;
+        let mut output = |value| total += value;
+        output.write(3);
+        output.write(4);
+        assert_eq!(total, 7);
+    }
+
+    #[test]
+    fn test_vec_output() {
+        let mut output = Vec::new();
+        output.write(1);
+        output.write(2);
+
+        assert_eq!(output, vec!(1, 2));
+    }
+}
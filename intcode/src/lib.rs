@@ -0,0 +1,48 @@
+//! The Intcode computer shared by the Advent of Code puzzles.
+
+/// The word stored in each memory cell and passed through input and output.
+pub type Value = i64;
+
+mod amplifier;
+mod assembler;
+mod cfg;
+mod computer;
+mod coverage;
+mod debugger;
+mod disassembler;
+mod error;
+#[cfg(test)]
+mod fixtures;
+mod instruction;
+mod io;
+mod loader;
+mod network;
+mod profile;
+mod runtime;
+mod scheduler;
+mod self_modification;
+mod snapshot;
+mod threaded;
+mod trace;
+mod watch;
+
+pub use amplifier::{best_chain, best_feedback_loop, permutations, run_chain, run_feedback_loop};
+pub use assembler::{assemble, AssembleError};
+pub use cfg::{control_flow_graph, BasicBlock, ControlFlowGraph, Edge, EdgeKind};
+pub use computer::{IntcodeComputer, Status};
+pub use coverage::{Coverage, OneWayJump};
+pub use debugger::Debugger;
+pub use disassembler::{disassemble, Line, Listing};
+pub use error::{ErrorKind, IntcodeError};
+pub use instruction::{Instruction, Parameter};
+pub use io::{Input, IterInput, Output};
+pub use loader::{load_program, parse_program};
+pub use network::{Network, NetworkReport, Packet, MONITOR_ADDRESS};
+pub use profile::{HotLoop, Profile};
+pub use runtime::{MachineError, Machines, Outcome};
+pub use scheduler::Scheduler;
+pub use self_modification::{CodeWrite, SelfModification};
+pub use snapshot::Snapshot;
+pub use threaded::ThreadedRuntime;
+pub use trace::{MemoryWrite, Operand, StepRecord};
+pub use watch::{Access, WatchAction, WatchEvent, Watchpoint};
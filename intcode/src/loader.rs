@@ -0,0 +1,40 @@
+use std::fs;
+use std::path::Path;
+
+use crate::Value;
+
+pub fn parse_program(source: &str) -> Result<Vec<Value>, &'static str> {
+    source.trim()
+        .split(',')
+        .map(|x| x.trim().parse().map_err(|_| "Invalid value in program"))
+        .collect()
+}
+
+pub fn load_program<P: AsRef<Path>>(path: P) -> Result<Vec<Value>, &'static str> {
+    let source = fs::read_to_string(path).map_err(|_| "Unable to read input file")?;
+    parse_program(&source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_program() {
+        let program = parse_program("1,9,10,3,\n2,3,11,0,99,30,40,50\n").unwrap();
+
+        assert_eq!(program, vec!(1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50));
+    }
+
+    #[test]
+    fn test_parse_program_negative_values() {
+        let program = parse_program("1101,100,-1,4,0").unwrap();
+
+        assert_eq!(program, vec!(1101, 100, -1, 4, 0));
+    }
+
+    #[test]
+    fn test_parse_program_invalid_value() {
+        assert_eq!(parse_program("1,2,x,4"), Err("Invalid value in program"));
+    }
+}
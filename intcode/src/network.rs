@@ -0,0 +1,216 @@
+use crate::computer::{IntcodeComputer, Status};
+use crate::error::{ErrorKind, IntcodeError};
+use crate::runtime::MachineError;
+use crate::Value;
+
+/// Packets sent here go to the monitor rather than a machine.
+pub const MONITOR_ADDRESS: usize = 255;
+
+const DEFAULT_QUANTUM: u64 = 1000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Packet {
+    pub destination: usize,
+    pub x: Value,
+    pub y: Value,
+}
+
+/// What the monitor saw while the network ran.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct NetworkReport {
+    /// Every packet sent to the monitor, in order.
+    pub monitor_packets: Vec<Packet>,
+    /// Every packet the monitor resent to machine 0 because the network was idle.
+    pub resent: Vec<Packet>,
+    /// The Y value the monitor resent twice in a row, which stops the network.
+    pub repeated: Option<Value>,
+}
+
+struct Machine {
+    computer: IntcodeComputer,
+    /// Output values of a packet that hasn't been completely sent yet.
+    partial: Vec<Value>,
+    halted: bool,
+}
+
+/// Machines running the same program that send each other packets as three outputs:
+/// destination, X and Y. Each machine is given its address as its first input, then reads a
+/// packet's X and Y, or -1 when none are waiting.
+pub struct Network {
+    machines: Vec<Machine>,
+    quantum: u64,
+}
+
+impl Network {
+    pub fn new(program: &[Value], size: usize) -> Network {
+        let machines = (0..size)
+            .map(|address| {
+                let mut computer = IntcodeComputer::new(program.to_vec());
+                computer.push_input(address as Value);
+                Machine { computer, partial: Vec::new(), halted: false }
+            })
+            .collect();
+        Network { machines, quantum: DEFAULT_QUANTUM }
+    }
+
+    /// Sets the most instructions a machine executes in one turn before the next machine gets
+    /// to run. A machine's turn also ends when it needs input or halts.
+    pub fn set_quantum(&mut self, steps: u64) {
+        self.quantum = steps.max(1);
+    }
+
+    /// Runs the machines in turn. When a whole round passes without any machine sending or
+    /// receiving a packet, the monitor resends the last packet it was sent to machine 0.
+    /// Stops when the monitor resends the same Y value twice in a row, when every machine has
+    /// halted, or when the network is idle and the monitor has nothing to send.
+    pub fn run(mut self) -> Result<NetworkReport, MachineError> {
+        let mut report = NetworkReport::default();
+
+        while !self.machines.is_empty() {
+            let mut idle = true;
+            for id in 0..self.machines.len() {
+                idle &= self.turn(id, &mut report).map_err(|error| MachineError { machine: id, error })?;
+            }
+
+            if self.machines.iter().all(|machine| machine.halted) {
+                break;
+            }
+            if !idle {
+                continue;
+            }
+
+            let packet = match report.monitor_packets.last() {
+                Some(&packet) => Packet { destination: 0, ..packet },
+                None => break,
+            };
+            let repeated = report.resent.last().map(|last| last.y) == Some(packet.y);
+            report.resent.push(packet);
+            self.deliver(packet);
+            if repeated {
+                report.repeated = Some(packet.y);
+                break;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Runs one machine for up to a quantum, or until it needs input it hasn't got. Returns
+    /// whether it was idle, neither receiving nor sending anything nor still running.
+    fn turn(&mut self, id: usize, report: &mut NetworkReport) -> Result<bool, IntcodeError> {
+        if self.machines[id].halted {
+            return Ok(true);
+        }
+
+        let mut idle = self.machines[id].computer.pending_inputs().is_empty();
+        if idle {
+            self.machines[id].computer.push_input(-1);
+        }
+
+        for _ in 0..self.quantum {
+            let machine = &mut self.machines[id];
+            let pc = machine.computer.pc();
+            match machine.computer.step()? {
+                Status::Output(value) => {
+                    idle = false;
+                    machine.partial.push(value);
+                    if machine.partial.len() == 3 {
+                        let packet = (machine.partial[0], machine.partial[1], machine.partial[2]);
+                        machine.partial.clear();
+                        self.send(pc, packet, report)?;
+                    }
+                },
+                Status::NeedsInput => return Ok(idle),
+                Status::Halted => {
+                    machine.halted = true;
+                    return Ok(idle);
+                },
+                Status::Running | Status::Watchpoint(_) => (),
+            }
+        }
+        // The machine is still busy when its turn runs out
+        Ok(false)
+    }
+
+    /// Routes a packet that the OUT instruction at `pc` finished sending.
+    fn send(
+        &mut self,
+        pc: usize,
+        (destination, x, y): (Value, Value, Value),
+        report: &mut NetworkReport,
+    ) -> Result<(), IntcodeError> {
+        if destination == MONITOR_ADDRESS as Value {
+            report.monitor_packets.push(Packet { destination: MONITOR_ADDRESS, x, y });
+        } else if destination >= 0 && (destination as usize) < self.machines.len() {
+            self.deliver(Packet { destination: destination as usize, x, y });
+        } else {
+            return Err(IntcodeError::new(pc, ErrorKind::UnknownDestination(destination)));
+        }
+        Ok(())
+    }
+
+    fn deliver(&mut self, packet: Packet) {
+        let computer = &mut self.machines[packet.destination].computer;
+        computer.push_input(packet.x);
+        computer.push_input(packet.y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Machine 0 starts by sending (1, 10, 20). Every machine sends the packets it receives on
+    // to the monitor with its address added to Y.
+    const FORWARD: [Value; 44] = [
+        3, 40, 1005, 40, 11, 104, 1, 104, 10, 104, 20, 3, 41, 1007, 41, 0, 42, 1005, 42, 11, 3, 43, 1, 43, 40, 43,
+        104, 255, 4, 41, 4, 43, 1105, 1, 11, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    #[test]
+    fn test_monitor() {
+        let report = Network::new(&FORWARD, 3).run().unwrap();
+        let resent = Packet { destination: 0, x: 10, y: 21 };
+
+        assert_eq!(report.monitor_packets, vec!(
+            Packet { destination: MONITOR_ADDRESS, x: 10, y: 21 },
+            Packet { destination: MONITOR_ADDRESS, x: 10, y: 21 },
+        ));
+        assert_eq!(report.resent, vec!(resent, resent));
+        assert_eq!(report.repeated, Some(21));
+    }
+
+    #[test]
+    fn test_idle_without_packets() {
+        // Reads forever without sending anything
+        let report = Network::new(&[3, 5, 1105, 1, 0, 0], 50).run().unwrap();
+
+        assert_eq!(report, NetworkReport::default());
+    }
+
+    #[test]
+    fn test_halted() {
+        let report = Network::new(&[3, 0, 99], 2).run().unwrap();
+
+        assert_eq!(report, NetworkReport::default());
+    }
+
+    #[test]
+    fn test_unknown_destination() {
+        let error = Network::new(&[3, 9, 104, 50, 104, 1, 104, 2, 99, 0], 4).run().unwrap_err();
+
+        assert_eq!(error, MachineError { machine: 0, error: IntcodeError::new(6, ErrorKind::UnknownDestination(50)) });
+        assert_eq!(error.to_string(), "Machine 0: Packet sent to unknown address 50 at pc 6");
+    }
+
+    #[test]
+    fn test_busy_machine_shares_time() {
+        // Machine 0 spins without reading input, machine 1 sends one packet to the monitor
+        let mut network = Network::new(&[3, 15, 1005, 15, 8, 1105, 1, 5, 104, 255, 104, 1, 104, 2, 99, 0], 2);
+        network.set_quantum(10);
+        let mut report = NetworkReport::default();
+
+        assert_eq!(network.turn(0, &mut report), Ok(false));
+        assert_eq!(network.turn(1, &mut report), Ok(false));
+        assert_eq!(report.monitor_packets, vec!(Packet { destination: MONITOR_ADDRESS, x: 1, y: 2 }));
+    }
+}
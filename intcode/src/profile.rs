@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::disassembler::Listing;
+use crate::instruction::Instruction;
+
+/// A loop found from a jump back to an earlier address.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HotLoop {
+    /// The address jumped back to.
+    pub start: usize,
+    /// The address of the jump instruction.
+    pub end: usize,
+    /// How many times the jump was taken.
+    pub iterations: u64,
+}
+
+/// Execution counts gathered while profiling is on.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Profile {
+    steps: u64,
+    addresses: HashMap<usize, u64>,
+    opcodes: HashMap<&'static str, u64>,
+    back_jumps: HashMap<(usize, usize), u64>,
+    branches: HashMap<usize, (u64, u64)>,
+}
+
+impl Profile {
+    /// Counts one executed instruction, `next_pc` being where execution carried on and
+    /// `jumped` whether it was a conditional jump that jumped.
+    pub(crate) fn record(&mut self, pc: usize, instruction: &Instruction, next_pc: usize, jumped: bool) {
+        self.steps += 1;
+        *self.addresses.entry(pc).or_insert(0) += 1;
+        *self.opcodes.entry(instruction.mnemonic()).or_insert(0) += 1;
+        if jumped && next_pc <= pc {
+            *self.back_jumps.entry((next_pc, pc)).or_insert(0) += 1;
+        }
+
+        if let Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) = instruction {
+            let (taken, not_taken) = self.branches.entry(pc).or_insert((0, 0));
+            if jumped {
+                *taken += 1;
+            } else {
+                *not_taken += 1;
+            }
+        }
+    }
+
+    /// The number of instructions counted. Unlike `IntcodeComputer::steps`, this includes the
+    /// HLT the machine stopped on, once for each time it halted.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// How many times the instruction at `address` was executed.
+    pub fn count(&self, address: usize) -> u64 {
+        self.addresses.get(&address).copied().unwrap_or(0)
+    }
+
+    pub fn opcode_count(&self, mnemonic: &str) -> u64 {
+        self.opcodes.get(mnemonic).copied().unwrap_or(0)
+    }
+
+    /// How many times the conditional jump at `address` was taken and not taken.
+    pub fn branch_counts(&self, address: usize) -> (u64, u64) {
+        self.branches.get(&address).copied().unwrap_or((0, 0))
+    }
+
+    /// Executed addresses with their counts, most executed first.
+    pub fn hot_addresses(&self) -> Vec<(usize, u64)> {
+        let mut addresses: Vec<(usize, u64)> = self.addresses.iter().map(|(&a, &c)| (a, c)).collect();
+        addresses.sort_by_key(|&(address, count)| (std::cmp::Reverse(count), address));
+        addresses
+    }
+
+    /// Loops with the most iterations first.
+    pub fn hot_loops(&self) -> Vec<HotLoop> {
+        let mut loops: Vec<HotLoop> = self.back_jumps.iter()
+            .map(|(&(start, end), &iterations)| HotLoop { start, end, iterations })
+            .collect();
+        loops.sort_by_key(|l| (std::cmp::Reverse(l.iterations), l.start, l.end));
+        loops
+    }
+
+    fn percent(&self, count: u64) -> f64 {
+        100.0 * count as f64 / self.steps.max(1) as f64
+    }
+
+    /// A ranked report of the opcodes, the `limit` busiest loops and the `limit` busiest
+    /// instructions, using `listing` to show what is at each address.
+    pub fn report(&self, listing: &Listing, limit: usize) -> String {
+        let mut report = String::new();
+        let line = |address: usize| match listing.line_index(address) {
+            Some(index) => listing.render(&listing.lines()[index]),
+            None => format!("{:>6}:", address),
+        };
+
+        writeln!(report, "Executed {} instructions", self.steps).unwrap();
+
+        writeln!(report, "\nOpcodes").unwrap();
+        let mut opcodes: Vec<(&str, u64)> = self.opcodes.iter().map(|(&m, &c)| (m, c)).collect();
+        opcodes.sort_by_key(|&(mnemonic, count)| (std::cmp::Reverse(count), mnemonic));
+        for (mnemonic, count) in opcodes {
+            writeln!(report, "{:>12} {:>6.1}%  {}", count, self.percent(count), mnemonic).unwrap();
+        }
+
+        writeln!(report, "\nHot loops").unwrap();
+        for hot_loop in self.hot_loops().iter().take(limit) {
+            writeln!(report, "{:>12} iterations of {}..={}", hot_loop.iterations, hot_loop.start, hot_loop.end).unwrap();
+        }
+
+        writeln!(report, "\nHot addresses").unwrap();
+        for (address, count) in self.hot_addresses().into_iter().take(limit) {
+            writeln!(report, "{:>12} {:>6.1}% {}", count, self.percent(count), line(address)).unwrap();
+        }
+        report
+    }
+
+    /// The whole listing with each line prefixed by how many times it was executed.
+    pub fn annotate(&self, listing: &Listing) -> String {
+        let mut annotated = String::new();
+        for line in listing.lines() {
+            if let Some(label) = listing.label(line.address()) {
+                writeln!(annotated, "{}:", label).unwrap();
+            }
+            match self.addresses.get(&line.address()) {
+                Some(count) => writeln!(annotated, "{:>12} {}", count, listing.render(line)).unwrap(),
+                None => writeln!(annotated, "{:>12} {}", "", listing.render(line)).unwrap(),
+            }
+        }
+        annotated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disassembler::disassemble;
+    use crate::instruction::Parameter;
+
+    fn add() -> Instruction {
+        Instruction::Add(Parameter::Immediate(1), Parameter::Immediate(1), Parameter::Position(3))
+    }
+
+    fn out() -> Instruction {
+        Instruction::Output(Parameter::Immediate(7))
+    }
+
+    fn jump() -> Instruction {
+        Instruction::JumpIfTrue(Parameter::Position(1), Parameter::Immediate(0))
+    }
+
+    fn profile() -> Profile {
+        // A pass through a three instruction loop at 0, 2 and 4 that jumps back twice
+        let mut profile = Profile::default();
+        for _ in 0..3 {
+            profile.record(0, &add(), 2, false);
+            profile.record(2, &out(), 4, false);
+            profile.record(4, &jump(), 0, true);
+        }
+        profile.record(7, &jump(), 7, true);
+        profile
+    }
+
+    #[test]
+    fn test_counts() {
+        let profile = profile();
+
+        assert_eq!(profile.steps(), 10);
+        assert_eq!(profile.count(2), 3);
+        assert_eq!(profile.count(3), 0);
+        assert_eq!(profile.opcode_count("JT"), 4);
+        assert_eq!(profile.opcode_count("MUL"), 0);
+        assert_eq!(profile.hot_addresses(), vec!((0, 3), (2, 3), (4, 3), (7, 1)));
+        assert_eq!(profile.hot_loops(), vec!(
+            HotLoop { start: 0, end: 4, iterations: 3 },
+            HotLoop { start: 7, end: 7, iterations: 1 },
+        ));
+    }
+
+    #[test]
+    fn test_branch_counts() {
+        let mut profile = Profile::default();
+        profile.record(4, &jump(), 0, true);
+        profile.record(4, &jump(), 7, false);
+        profile.record(4, &jump(), 0, true);
+        profile.record(0, &add(), 4, false);
+
+        assert_eq!(profile.branch_counts(4), (2, 1));
+        assert_eq!(profile.branch_counts(0), (0, 0));
+
+        // A jump to the next instruction still counts as taken
+        profile.record(7, &jump(), 10, true);
+        assert_eq!(profile.branch_counts(7), (1, 0));
+    }
+
+    #[test]
+    fn test_report() {
+        let listing = disassemble(&[1101, 1, 1, 3, 104, 7, 1105, 1, 0]);
+        let mut profile = Profile::default();
+        for _ in 0..4 {
+            profile.record(0, &add(), 4, false);
+            profile.record(4, &out(), 6, false);
+            profile.record(6, &jump(), 0, true);
+        }
+        profile.record(0, &add(), 4, false);
+
+        assert_eq!(profile.report(&listing, 2), "\
+Executed 13 instructions
+
+Opcodes
+           5   38.5%  ADD
+           4   30.8%  JT
+           4   30.8%  OUT
+
+Hot loops
+           4 iterations of 0..=6
+
+Hot addresses
+           5   38.5%      0: 1101 1 1 3               ADD  #1, #1, [3]
+           4   30.8%      4: 104 7                    OUT  #7
+");
+
+        assert_eq!(profile.annotate(&listing), "\
+             L0:
+           5      0: 1101 1 1 3               ADD  #1, #1, [3]
+           4      4: 104 7                    OUT  #7
+           4      6: 1105 1 0                 JT   #1, #L0
+");
+    }
+}
@@ -0,0 +1,124 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::computer::IntcodeComputer;
+use crate::error::IntcodeError;
+use crate::Value;
+
+/// A group of machines and where each one sends its output, ready to be run by a
+/// `ThreadedRuntime` or a `Scheduler`. A machine without a connection keeps its output.
+#[derive(Debug, Default)]
+pub struct Machines {
+    computers: Vec<IntcodeComputer>,
+    targets: Vec<Option<usize>>,
+}
+
+impl Machines {
+    pub fn new() -> Machines {
+        Machines::default()
+    }
+
+    /// Adds a machine, returning the id used to connect it.
+    pub fn add(&mut self, computer: IntcodeComputer) -> usize {
+        self.computers.push(computer);
+        self.targets.push(None);
+        self.computers.len() - 1
+    }
+
+    /// Sends everything `from` outputs to the input of `to`, which can be itself.
+    pub fn connect(&mut self, from: usize, to: usize) {
+        assert!(from < self.len(), "No machine {}", from);
+        assert!(to < self.len(), "No machine {}", to);
+        self.targets[from] = Some(to);
+    }
+
+    pub fn len(&self) -> usize {
+        self.computers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.computers.is_empty()
+    }
+
+    /// Each machine with the id of the machine it sends its output to.
+    pub(crate) fn into_parts(self) -> impl Iterator<Item = (IntcodeComputer, Option<usize>)> {
+        self.computers.into_iter().zip(self.targets)
+    }
+}
+
+/// How a group of connected machines finished.
+#[derive(Debug)]
+pub struct Outcome {
+    /// The machines in the order they were added. Values sent to a machine that it never
+    /// read are left in its pending inputs.
+    pub machines: Vec<IntcodeComputer>,
+    /// What each machine output without a connection to send it to.
+    pub outputs: Vec<Vec<Value>>,
+    /// Every machine that hadn't halted was waiting for input that could never arrive.
+    pub deadlocked: bool,
+}
+
+/// A machine that failed, stopping the whole group.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MachineError {
+    pub machine: usize,
+    pub error: IntcodeError,
+}
+
+impl fmt::Display for MachineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Machine {}: {}", self.machine, self.error)
+    }
+}
+
+impl Error for MachineError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{amplifiers, AMPLIFIER_CHAIN, AMPLIFIER_FEEDBACK, READS_TWO_SENDS_ONE};
+    use crate::scheduler::Scheduler;
+    use crate::threaded::ThreadedRuntime;
+
+    /// Runs the machines with each runtime, which should all give the same outcome.
+    fn run_all(program: &[Value], phases: &[Value], feedback: bool) -> Vec<Outcome> {
+        vec!(
+            ThreadedRuntime::new(amplifiers(program, phases, feedback)).run().unwrap(),
+            Scheduler::new(amplifiers(program, phases, feedback)).run().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_chain() {
+        for outcome in run_all(&AMPLIFIER_CHAIN, &[4, 3, 2, 1, 0], false) {
+            assert!(!outcome.deadlocked);
+            assert_eq!(outcome.outputs, vec!(vec!(), vec!(), vec!(), vec!(), vec!(43210)));
+        }
+    }
+
+    #[test]
+    fn test_feedback_loop() {
+        for outcome in run_all(&AMPLIFIER_FEEDBACK, &[9, 8, 7, 6, 5], true) {
+            assert!(!outcome.deadlocked);
+            assert!(outcome.outputs.iter().all(|outputs| outputs.is_empty()));
+            assert_eq!(outcome.machines[0].pending_inputs(), &[139629729]);
+        }
+    }
+
+    #[test]
+    fn test_deadlock() {
+        for outcome in run_all(&READS_TWO_SENDS_ONE, &[1, 2], true) {
+            assert!(outcome.deadlocked);
+            assert_eq!(outcome.machines[0].pc(), 4);
+            assert_eq!(outcome.machines[1].pc(), 2);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "No machine 2")]
+    fn test_connect_checks_from() {
+        let mut machines = Machines::new();
+        machines.add(IntcodeComputer::new(vec!(99)));
+        machines.connect(2, 0);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::computer::{IntcodeComputer, Status};
+use crate::runtime::{MachineError, Machines, Outcome};
+use crate::Value;
+
+const DEFAULT_QUANTUM: u64 = 1000;
+
+/// A machine with what the scheduler knows about it.
+struct Machine {
+    computer: IntcodeComputer,
+    target: Option<usize>,
+    outputs: Vec<Value>,
+    halted: bool,
+}
+
+/// Runs machines in turn on the current thread, so the same machines always give the same
+/// result. Outputs go straight into the input queue of the connected machine.
+pub struct Scheduler {
+    machines: Vec<Machine>,
+    quantum: u64,
+}
+
+impl Scheduler {
+    pub fn new(machines: Machines) -> Scheduler {
+        let machines = machines.into_parts()
+            .map(|(computer, target)| Machine { computer, target, outputs: Vec::new(), halted: false })
+            .collect();
+        Scheduler { machines, quantum: DEFAULT_QUANTUM }
+    }
+
+    /// Sets the most instructions a machine executes in one turn before the next machine gets
+    /// to run. A machine's turn also ends when it needs input or halts.
+    pub fn set_quantum(&mut self, steps: u64) {
+        self.quantum = steps.max(1);
+    }
+
+    /// Gives each machine a turn until they have all halted, or until a whole round passes in
+    /// which every machine that hasn't halted is waiting for input.
+    pub fn run(mut self) -> Result<Outcome, MachineError> {
+        loop {
+            let mut progressed = false;
+            for id in 0..self.machines.len() {
+                progressed |= self.turn(id).map_err(|error| MachineError { machine: id, error })?;
+            }
+
+            let halted = self.machines.iter().all(|machine| machine.halted);
+            if halted || !progressed {
+                let (machines, outputs) = self.machines.into_iter()
+                    .map(|machine| (machine.computer, machine.outputs))
+                    .unzip();
+                break Ok(Outcome { machines, outputs, deadlocked: !halted });
+            }
+        }
+    }
+
+    /// Runs one machine for up to a quantum, returning whether it did anything.
+    fn turn(&mut self, id: usize) -> Result<bool, crate::IntcodeError> {
+        let mut progressed = false;
+        let target = self.machines[id].target;
+
+        for _ in 0..self.quantum {
+            if self.machines[id].halted {
+                break;
+            }
+            match self.machines[id].computer.step()? {
+                Status::Running | Status::Watchpoint(_) => (),
+                Status::Output(value) => match target {
+                    Some(target) => self.machines[target].computer.push_input(value),
+                    None => self.machines[id].outputs.push(value),
+                },
+                Status::NeedsInput => break,
+                Status::Halted => {
+                    self.machines[id].halted = true;
+                    break;
+                },
+            }
+            progressed = true;
+        }
+        Ok(progressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ErrorKind, IntcodeError};
+    use crate::fixtures::{amplifiers, AMPLIFIER_FEEDBACK};
+
+    #[test]
+    fn test_deterministic() {
+        let run = |quantum| {
+            let mut scheduler = Scheduler::new(amplifiers(&AMPLIFIER_FEEDBACK, &[9, 7, 8, 5, 6], true));
+            scheduler.set_quantum(quantum);
+            let outcome = scheduler.run().unwrap();
+            outcome.machines.iter().map(|machine| machine.snapshot()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(1), run(1));
+        assert_eq!(run(1), run(1000));
+    }
+
+    #[test]
+    fn test_quantum_shares_time() {
+        // The first machine never halts, but the second still gets to fail
+        let mut machines = Machines::new();
+        machines.add(IntcodeComputer::new(vec!(1105, 1, 0)));
+        machines.add(IntcodeComputer::new(vec!(104, 1, 42)));
+        let mut scheduler = Scheduler::new(machines);
+        scheduler.set_quantum(10);
+
+        assert_eq!(
+            scheduler.run().unwrap_err(),
+            MachineError { machine: 1, error: IntcodeError::new(2, ErrorKind::UnknownOpcode(42)) }
+        );
+    }
+}
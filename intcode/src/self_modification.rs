@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::error::ErrorKind;
+use crate::Value;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SelfModification {
+    /// Keep a list of writes to executed code and carry on.
+    Report,
+    /// Fail with `SelfModifyingCode` before the write happens.
+    Fail,
+}
+
+/// A write to an address that had already been executed as part of an instruction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CodeWrite {
+    pub step: u64,
+    pub pc: usize,
+    pub address: usize,
+    pub old: Value,
+    pub new: Value,
+}
+
+impl fmt::Display for CodeWrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pc {} wrote {} over code {} at {} in step {}", self.pc, self.new, self.old, self.address, self.step)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CodeTracker {
+    action: SelfModification,
+    executed: HashSet<usize>,
+    writes: Vec<CodeWrite>,
+}
+
+impl CodeTracker {
+    pub(crate) fn new(action: SelfModification) -> CodeTracker {
+        CodeTracker { action, executed: HashSet::new(), writes: Vec::new() }
+    }
+
+    pub(crate) fn executed(&mut self, address: usize, size: usize) {
+        self.executed.extend(address..address + size);
+    }
+
+    /// Checks a write that is about to happen.
+    pub(crate) fn check(&mut self, write: CodeWrite) -> Result<(), ErrorKind> {
+        if !self.executed.contains(&write.address) {
+            return Ok(());
+        }
+        match self.action {
+            SelfModification::Report => {
+                self.writes.push(write);
+                Ok(())
+            },
+            SelfModification::Fail => Err(ErrorKind::SelfModifyingCode(write.address)),
+        }
+    }
+
+    pub(crate) fn writes(&self) -> &[CodeWrite] {
+        &self.writes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(address: usize) -> CodeWrite {
+        CodeWrite { step: 4, pc: 10, address, old: 99, new: 1 }
+    }
+
+    #[test]
+    fn test_report() {
+        let mut tracker = CodeTracker::new(SelfModification::Report);
+        tracker.executed(2, 3);
+
+        assert_eq!(tracker.check(write(1)), Ok(()));
+        assert_eq!(tracker.check(write(4)), Ok(()));
+        assert_eq!(tracker.check(write(5)), Ok(()));
+        assert_eq!(tracker.writes(), &[write(4)]);
+    }
+
+    #[test]
+    fn test_fail() {
+        let mut tracker = CodeTracker::new(SelfModification::Fail);
+        tracker.executed(0, 1);
+
+        assert_eq!(tracker.check(write(1)), Ok(()));
+        assert_eq!(tracker.check(write(0)), Err(ErrorKind::SelfModifyingCode(0)));
+        assert_eq!(tracker.writes(), &[]);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(write(3).to_string(), "pc 10 wrote 1 over code 99 at 3 in step 4");
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::Value;
+
+const HEADER: &str = "intcode-snapshot 1";
+
+/// The full state of an `IntcodeComputer`, enough to carry on exactly where it stopped.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Snapshot {
+    pub pc: usize,
+    pub relative_base: Value,
+    pub steps: u64,
+    /// Values pushed with `push_input` that the program hasn't read yet.
+    pub inputs: Vec<Value>,
+    pub memory: Vec<Value>,
+}
+
+impl Snapshot {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), &'static str> {
+        fs::write(path, self.to_string()).map_err(|_| "Unable to write snapshot file")
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Snapshot, &'static str> {
+        fs::read_to_string(path).map_err(|_| "Unable to read snapshot file")?.parse()
+    }
+}
+
+fn join(values: &[Value]) -> String {
+    values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",")
+}
+
+fn split(values: &str) -> Result<Vec<Value>, &'static str> {
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+    values.split(',')
+        .map(|x| x.parse().map_err(|_| "Invalid value in snapshot"))
+        .collect()
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "pc {}", self.pc)?;
+        writeln!(f, "relative_base {}", self.relative_base)?;
+        writeln!(f, "steps {}", self.steps)?;
+        writeln!(f, "inputs {}", join(&self.inputs))?;
+        writeln!(f, "memory {}", join(&self.memory))
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Snapshot, &'static str> {
+        let mut lines = s.lines();
+        if lines.next() != Some(HEADER) {
+            return Err("Not an Intcode snapshot");
+        }
+
+        let mut field = |name: &str| {
+            let line = lines.next().ok_or("Snapshot is incomplete")?;
+            let mut parts = line.splitn(2, ' ');
+            match (parts.next(), parts.next()) {
+                (Some(key), value) if key == name => Ok(value.unwrap_or("").trim().to_string()),
+                _ => Err("Unexpected field in snapshot"),
+            }
+        };
+
+        Ok(Snapshot {
+            pc: field("pc")?.parse().map_err(|_| "Invalid pc in snapshot")?,
+            relative_base: field("relative_base")?.parse().map_err(|_| "Invalid relative base in snapshot")?,
+            steps: field("steps")?.parse().map_err(|_| "Invalid step count in snapshot")?,
+            inputs: split(&field("inputs")?)?,
+            memory: split(&field("memory")?)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            pc: 4,
+            relative_base: -3,
+            steps: 17,
+            inputs: vec!(5, -6),
+            memory: vec!(1101, 1, 2, 3, 99),
+        }
+    }
+
+    #[test]
+    fn test_to_string() {
+        assert_eq!(
+            snapshot().to_string(),
+            "intcode-snapshot 1\npc 4\nrelative_base -3\nsteps 17\ninputs 5,-6\nmemory 1101,1,2,3,99\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        assert_eq!(snapshot().to_string().parse(), Ok(snapshot()));
+    }
+
+    #[test]
+    fn test_parse_no_inputs() {
+        let snapshot: Snapshot = "intcode-snapshot 1\npc 0\nrelative_base 0\nsteps 0\ninputs \nmemory 99\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(snapshot.inputs, vec!());
+        assert_eq!(snapshot.memory, vec!(99));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("1,2,3".parse::<Snapshot>(), Err("Not an Intcode snapshot"));
+        assert_eq!("intcode-snapshot 1\npc 0\n".parse::<Snapshot>(), Err("Snapshot is incomplete"));
+        assert_eq!("intcode-snapshot 1\npc x\n".parse::<Snapshot>(), Err("Invalid pc in snapshot"));
+        assert_eq!(
+            "intcode-snapshot 1\nrelative_base 0\n".parse::<Snapshot>(),
+            Err("Unexpected field in snapshot")
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("intcode-snapshot-{}.txt", std::process::id()));
+        snapshot().save(&path).unwrap();
+        let loaded = Snapshot::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, Ok(snapshot()));
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+use crate::computer::{IntcodeComputer, Status};
+use crate::error::IntcodeError;
+use crate::runtime::{MachineError, Machines, Outcome};
+use crate::Value;
+
+enum Message {
+    Value(Value),
+    Stop,
+}
+
+/// What the machine threads tell the supervisor.
+enum Event {
+    Sent(usize),
+    Received(usize),
+    Waiting(usize),
+    Halted(usize),
+    Failed(usize, IntcodeError),
+}
+
+/// Runs each machine on its own thread, with outputs passed to the connected machine's input
+/// over a channel.
+///
+/// A supervisor follows every machine and every value sent. It stops the machines once they
+/// have all halted, or once every machine still running is waiting for input and no values
+/// are on their way to it.
+pub struct ThreadedRuntime {
+    machines: Machines,
+}
+
+struct Finished {
+    computer: IntcodeComputer,
+    input: Receiver<Message>,
+    outputs: Vec<Value>,
+}
+
+fn run_machine(
+    id: usize,
+    mut computer: IntcodeComputer,
+    input: Receiver<Message>,
+    output: Option<(usize, Sender<Message>)>,
+    events: Sender<Event>,
+    stop: Arc<AtomicBool>,
+) -> Finished {
+    let mut outputs = Vec::new();
+
+    // Sends only fail once the supervisor is done with every machine, so errors are ignored
+    while !stop.load(Ordering::Relaxed) {
+        match computer.step() {
+            Ok(Status::Running) | Ok(Status::Watchpoint(_)) => (),
+            Ok(Status::Output(value)) => match &output {
+                Some((target, sender)) => {
+                    let _ = events.send(Event::Sent(*target));
+                    let _ = sender.send(Message::Value(value));
+                },
+                None => outputs.push(value),
+            },
+            Ok(Status::NeedsInput) => {
+                let _ = events.send(Event::Waiting(id));
+                match input.recv() {
+                    Ok(Message::Value(value)) => {
+                        let _ = events.send(Event::Received(id));
+                        computer.push_input(value);
+                    },
+                    Ok(Message::Stop) | Err(_) => break,
+                }
+            },
+            Ok(Status::Halted) => {
+                let _ = events.send(Event::Halted(id));
+                break;
+            },
+            Err(error) => {
+                let _ = events.send(Event::Failed(id, error));
+                break;
+            },
+        }
+    }
+
+    Finished { computer, input, outputs }
+}
+
+impl ThreadedRuntime {
+    pub fn new(machines: Machines) -> ThreadedRuntime {
+        ThreadedRuntime { machines }
+    }
+
+    /// Runs every machine until the supervisor stops them, or one of them fails.
+    pub fn run(self) -> Result<Outcome, MachineError> {
+        let count = self.machines.len();
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..count).map(|_| channel()).unzip();
+        let (events, supervisor) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handles: Vec<_> = self.machines.into_parts()
+            .zip(receivers)
+            .enumerate()
+            .map(|(id, ((computer, target), input))| {
+                let output = target.map(|target| (target, senders[target].clone()));
+                let (events, stop) = (events.clone(), stop.clone());
+                thread::spawn(move || run_machine(id, computer, input, output, events, stop))
+            })
+            .collect();
+        drop(events);
+
+        let mut halted = vec!(false; count);
+        let mut waiting = vec!(false; count);
+        let mut in_flight = vec!(0; count);
+        let mut failure = None;
+
+        let blocked = |halted: &[bool], waiting: &[bool], in_flight: &[usize]| {
+            (0..count).all(|id| halted[id] || (waiting[id] && in_flight[id] == 0))
+        };
+        while count > 0 && !blocked(&halted, &waiting, &in_flight) {
+            match supervisor.recv().expect("machines only stop when told to") {
+                Event::Sent(target) => in_flight[target] += 1,
+                Event::Received(id) => {
+                    in_flight[id] -= 1;
+                    waiting[id] = false;
+                },
+                Event::Waiting(id) => waiting[id] = true,
+                Event::Halted(id) => halted[id] = true,
+                Event::Failed(machine, error) => {
+                    failure = Some(MachineError { machine, error });
+                    break;
+                },
+            }
+        }
+
+        stop.store(true, Ordering::Relaxed);
+        for sender in &senders {
+            let _ = sender.send(Message::Stop);
+        }
+
+        let mut machines = Vec::with_capacity(count);
+        let mut outputs = Vec::with_capacity(count);
+        for handle in handles {
+            let mut finished = handle.join().expect("machine thread panicked");
+            while let Ok(message) = finished.input.try_recv() {
+                if let Message::Value(value) = message {
+                    finished.computer.push_input(value);
+                }
+            }
+            machines.push(finished.computer);
+            outputs.push(finished.outputs);
+        }
+
+        match failure {
+            Some(failure) => Err(failure),
+            None => Ok(Outcome { machines, outputs, deadlocked: !halted.iter().all(|&h| h) }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn test_failure_stops_everything() {
+        let mut machines = Machines::new();
+        machines.add(IntcodeComputer::new(vec!(1105, 1, 0)));
+        machines.add(IntcodeComputer::new(vec!(104, 1, 42)));
+
+        assert_eq!(
+            ThreadedRuntime::new(machines).run().unwrap_err(),
+            MachineError { machine: 1, error: IntcodeError::new(2, ErrorKind::UnknownOpcode(42)) }
+        );
+    }
+
+    #[test]
+    fn test_no_machines() {
+        let outcome = ThreadedRuntime::new(Machines::new()).run().unwrap();
+
+        assert!(outcome.machines.is_empty());
+        assert!(!outcome.deadlocked);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::instruction::{Instruction, Parameter};
+use crate::Value;
+
+/// A parameter after it has been resolved against memory and the relative base.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Operand {
+    /// The memory cell the parameter refers to, or `None` for immediate parameters.
+    pub address: Option<usize>,
+    /// The value read, or for a parameter that is written to, the value stored.
+    pub value: Value,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MemoryWrite {
+    pub address: usize,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Everything a single executed instruction did.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StepRecord {
+    /// The number of instructions executed before this one.
+    pub step: u64,
+    pub pc: usize,
+    /// The relative base the instruction's parameters were resolved with.
+    pub relative_base: Value,
+    pub instruction: Instruction,
+    /// One entry per parameter used, in order. The target of a jump that isn't taken is never
+    /// read, so it has no entry.
+    pub operands: Vec<Operand>,
+    pub writes: Vec<MemoryWrite>,
+    pub input: Option<Value>,
+    pub output: Option<Value>,
+}
+
+impl StepRecord {
+    pub(crate) fn new(step: u64, pc: usize, relative_base: Value, instruction: Instruction) -> StepRecord {
+        StepRecord {
+            step,
+            pc,
+            relative_base,
+            instruction,
+            operands: Vec::new(),
+            writes: Vec::new(),
+            input: None,
+            output: None,
+        }
+    }
+
+    /// Renders the record as a single line of JSON. The `input` and `output` keys are only
+    /// present for instructions that read or wrote a value.
+    pub fn to_json(&self) -> String {
+        let parameters = self.instruction.parameters();
+        let operands: Vec<String> = self.operands.iter().zip(&parameters)
+            .map(|(operand, parameter)| {
+                let mode = match parameter {
+                    Parameter::Position(_) => "position",
+                    Parameter::Immediate(_) => "immediate",
+                    Parameter::Relative(_) => "relative",
+                };
+                match operand.address {
+                    Some(address) => {
+                        format!("{{\"mode\":\"{}\",\"address\":{},\"value\":{}}}", mode, address, operand.value)
+                    },
+                    None => format!("{{\"mode\":\"{}\",\"value\":{}}}", mode, operand.value),
+                }
+            })
+            .collect();
+        let writes: Vec<String> = self.writes.iter()
+            .map(|write| format!("{{\"address\":{},\"old\":{},\"new\":{}}}", write.address, write.old, write.new))
+            .collect();
+        let mut json = format!(
+            "{{\"step\":{},\"pc\":{},\"relative_base\":{},\"instruction\":\"{}\",\"operands\":[{}],\"writes\":[{}]",
+            self.step,
+            self.pc,
+            self.relative_base,
+            self.instruction,
+            operands.join(","),
+            writes.join(","),
+        );
+        if let Some(input) = self.input {
+            json.push_str(&format!(",\"input\":{}", input));
+        }
+        if let Some(output) = self.output {
+            json.push_str(&format!(",\"output\":{}", output));
+        }
+        json.push('}');
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let mut record = StepRecord::new(
+            7,
+            5,
+            -2,
+            Instruction::Multiply(Parameter::Immediate(2), Parameter::Position(15), Parameter::Relative(3)),
+        );
+        record.operands = vec!(
+            Operand { address: None, value: 2 },
+            Operand { address: Some(15), value: 4 },
+            Operand { address: Some(1), value: 8 },
+        );
+        record.writes = vec!(MemoryWrite { address: 1, old: 15, new: 8 });
+
+        assert_eq!(record.to_json(), concat!(
+            "{\"step\":7,\"pc\":5,\"relative_base\":-2,\"instruction\":\"MUL  #2, [15], [rb+3]\",",
+            "\"operands\":[{\"mode\":\"immediate\",\"value\":2},{\"mode\":\"position\",\"address\":15,\"value\":4},",
+            "{\"mode\":\"relative\",\"address\":1,\"value\":8}],",
+            "\"writes\":[{\"address\":1,\"old\":15,\"new\":8}]}",
+        ));
+    }
+
+    #[test]
+    fn test_to_json_io() {
+        let mut record = StepRecord::new(0, 0, 0, Instruction::Input(Parameter::Position(3)));
+        record.operands = vec!(Operand { address: Some(3), value: -5 });
+        record.writes = vec!(MemoryWrite { address: 3, old: 0, new: -5 });
+        record.input = Some(-5);
+
+        assert_eq!(
+            record.to_json(),
+            "{\"step\":0,\"pc\":0,\"relative_base\":0,\"instruction\":\"IN   [3]\",\
+             \"operands\":[{\"mode\":\"position\",\"address\":3,\"value\":-5}],\
+             \"writes\":[{\"address\":3,\"old\":0,\"new\":-5}],\"input\":-5}"
+        );
+    }
+
+    #[test]
+    fn test_to_json_no_operands() {
+        let record = StepRecord::new(3, 9, 0, Instruction::Halt);
+
+        assert_eq!(
+            record.to_json(),
+            "{\"step\":3,\"pc\":9,\"relative_base\":0,\"instruction\":\"HLT\",\"operands\":[],\"writes\":[]}"
+        );
+    }
+}
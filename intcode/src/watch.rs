@@ -0,0 +1,156 @@
+use std::fmt;
+
+use crate::trace::StepRecord;
+use crate::Value;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WatchAction {
+    /// Stop with `Status::Watchpoint` after the instruction that touched the address.
+    Halt,
+    /// Carry on, keeping the access in the computer's watch log.
+    Log,
+}
+
+/// Watches the addresses from `start` to `end` inclusive.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Watchpoint {
+    pub start: usize,
+    pub end: usize,
+    pub read: bool,
+    pub write: bool,
+    pub action: WatchAction,
+}
+
+impl Watchpoint {
+    fn matches(&self, event: &WatchEvent) -> bool {
+        let access = match event.access {
+            Access::Read => self.read,
+            Access::Write => self.write,
+        };
+        access && self.start <= event.address && event.address <= self.end
+    }
+}
+
+/// A watched address being read or written.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WatchEvent {
+    pub step: u64,
+    pub pc: usize,
+    pub address: usize,
+    pub access: Access,
+    /// For reads the old and new values are both the value read.
+    pub old: Value,
+    pub new: Value,
+}
+
+impl fmt::Display for WatchEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.access {
+            Access::Read => write!(f, "read {} from {} at pc {}", self.old, self.address, self.pc),
+            Access::Write => write!(f, "wrote {} to {} at pc {}, was {}", self.new, self.address, self.pc, self.old),
+        }
+    }
+}
+
+/// Every memory access an executed instruction made, in the order they happened.
+fn accesses(record: &StepRecord) -> Vec<WatchEvent> {
+    // Instructions only ever write to their last parameter
+    let reads = if record.writes.is_empty() {
+        &record.operands[..]
+    } else {
+        &record.operands[..record.operands.len() - 1]
+    };
+    let event = |address, access, old, new| WatchEvent { step: record.step, pc: record.pc, address, access, old, new };
+
+    reads.iter()
+        .filter_map(|operand| operand.address.map(|address| event(address, Access::Read, operand.value, operand.value)))
+        .chain(record.writes.iter().map(|write| event(write.address, Access::Write, write.old, write.new)))
+        .collect()
+}
+
+/// Splits the accesses in `record` that `watchpoints` cover into those that halt and those
+/// that are only logged. An access covered by both kinds of watchpoint goes in both.
+pub(crate) fn check(watchpoints: &[Watchpoint], record: &StepRecord) -> (Vec<WatchEvent>, Vec<WatchEvent>) {
+    let mut halts = Vec::new();
+    let mut logs = Vec::new();
+
+    for event in accesses(record) {
+        let matching = || watchpoints.iter().filter(|watchpoint| watchpoint.matches(&event));
+        if matching().any(|watchpoint| watchpoint.action == WatchAction::Halt) {
+            halts.push(event);
+        }
+        if matching().any(|watchpoint| watchpoint.action == WatchAction::Log) {
+            logs.push(event);
+        }
+    }
+    (halts, logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::{Instruction, Parameter};
+    use crate::trace::{MemoryWrite, Operand};
+
+    fn record() -> StepRecord {
+        // ADD [4], #3, [4] with 4 holding 7
+        let mut record = StepRecord::new(
+            2,
+            8,
+            0,
+            Instruction::Add(Parameter::Position(4), Parameter::Immediate(3), Parameter::Position(4)),
+        );
+        record.operands = vec!(
+            Operand { address: Some(4), value: 7 },
+            Operand { address: None, value: 3 },
+            Operand { address: Some(4), value: 10 },
+        );
+        record.writes = vec!(MemoryWrite { address: 4, old: 7, new: 10 });
+        record
+    }
+
+    fn watchpoint(start: usize, end: usize, read: bool, write: bool, action: WatchAction) -> Watchpoint {
+        Watchpoint { start, end, read, write, action }
+    }
+
+    #[test]
+    fn test_accesses() {
+        assert_eq!(accesses(&record()), vec!(
+            WatchEvent { step: 2, pc: 8, address: 4, access: Access::Read, old: 7, new: 7 },
+            WatchEvent { step: 2, pc: 8, address: 4, access: Access::Write, old: 7, new: 10 },
+        ));
+    }
+
+    #[test]
+    fn test_check() {
+        let read = WatchEvent { step: 2, pc: 8, address: 4, access: Access::Read, old: 7, new: 7 };
+        let write = WatchEvent { access: Access::Write, new: 10, ..read };
+
+        assert_eq!(check(&[], &record()), (vec!(), vec!()));
+        assert_eq!(check(&[watchpoint(5, 9, true, true, WatchAction::Halt)], &record()), (vec!(), vec!()));
+        assert_eq!(check(&[watchpoint(0, 4, false, true, WatchAction::Halt)], &record()), (vec!(write), vec!()));
+        assert_eq!(check(&[watchpoint(4, 4, true, false, WatchAction::Log)], &record()), (vec!(), vec!(read)));
+        assert_eq!(
+            check(&[
+                watchpoint(4, 4, true, true, WatchAction::Log),
+                watchpoint(3, 5, false, true, WatchAction::Halt),
+            ], &record()),
+            (vec!(write), vec!(read, write))
+        );
+    }
+
+    #[test]
+    fn test_event_display() {
+        let read = WatchEvent { step: 2, pc: 8, address: 4, access: Access::Read, old: 7, new: 7 };
+        let write = WatchEvent { access: Access::Write, new: 10, ..read };
+
+        assert_eq!(read.to_string(), "read 7 from 4 at pc 8");
+        assert_eq!(write.to_string(), "wrote 10 to 4 at pc 8, was 7");
+    }
+}